@@ -1,6 +1,9 @@
+use super::hooks::{run_hook, HookContext};
+use super::output::Output;
 use crate::color::Color;
+use crate::config::{Config, Hook, Hooks, PlaceConfig, Project};
+use anyhow::Context;
 use clap::Parser;
-use std::path::Path;
 use std::process::Command;
 
 use fs_err as fs;
@@ -17,39 +20,100 @@ pub struct BuildCommand {
 }
 
 impl BuildCommand {
-    pub fn run(&self) -> anyhow::Result<Option<String>> {
-        build_place(self.project_name.clone(), self.output_name.clone());
+    pub fn run(&self, project: &Project) -> anyhow::Result<Option<String>> {
+        let data = Config::load_data(project)?;
+        let target = BuildTarget::new(self.project_name.clone(), self.output_name.clone());
+        build_place(
+            project,
+            &target,
+            &data.hooks,
+            &HookContext::default(),
+            &Output::default(),
+        )?;
         Ok(None)
     }
 }
 
-fn build_output(project: String, output_path: String) -> String {
-    format!(
-        "{} {} ({})",
-        Color::green().pad("Building"),
-        project,
-        output_path
-    )
+/// A project file and the place file it is built to, relative to the project root
+#[derive(Debug, Clone)]
+pub struct BuildTarget {
+    pub name: String,
+    pub project_file: String,
+    pub output: String,
 }
 
-pub fn build_place(project_name: Option<String>, output_name: Option<String>) -> Option<String> {
-    let project = project_name.unwrap_or("default".to_string());
-    let output = format!("build/{}.rbxl", output_name.unwrap_or(project.clone()));
-    let path = Path::new(&output).parent().unwrap();
+impl BuildTarget {
+    pub fn new(project_name: Option<String>, output_name: Option<String>) -> Self {
+        let name = project_name.unwrap_or("default".to_string());
+        let output = format!("build/{}.rbxl", output_name.unwrap_or(name.clone()));
+
+        BuildTarget {
+            project_file: format!("{name}.project.json"),
+            name,
+            output,
+        }
+    }
+
+    /// The target for a deployment place, following its config settings
+    pub fn for_place(name: &str, place: &PlaceConfig) -> Self {
+        BuildTarget {
+            name: name.to_string(),
+            project_file: place.project_file(name),
+            output: format!(
+                "build/deploy/{}.{}",
+                name,
+                place.output.unwrap_or_default().extension()
+            ),
+        }
+    }
+}
+
+/// Builds a target with rojo, running the build hooks around it
+pub fn build_place(
+    project: &Project,
+    target: &BuildTarget,
+    hooks: &Hooks,
+    context: &HookContext,
+    out: &Output,
+) -> anyhow::Result<String> {
+    let output_path = project.path(&target.output);
+    let path = output_path.parent().unwrap();
+
+    let context = HookContext {
+        place_name: Some(target.name.clone()),
+        artifact: Some(output_path.to_string_lossy().to_string()),
+        ..context.clone()
+    };
+    run_hook(project, hooks, Hook::PreBuild, &context, out)?;
 
     if !path.exists() {
-        fs::create_dir_all(path).expect("failed to create directory");
+        fs::create_dir_all(path)?;
     };
 
-    println!("{}", build_output(project.clone(), output.clone()));
-    Command::new("sh")
+    out.status(
+        Color::green(),
+        "Building",
+        format!("{} ({})", target.name, target.output),
+    );
+    let build = Command::new("sh")
         .arg("-c")
         .arg(format!(
-            r#"rojo --version && rojo build "{}.project.json" -o "{}""#,
-            project, output,
+            r#"rojo --version && rojo build "{}" -o "{}""#,
+            target.project_file, target.output,
         ))
+        .current_dir(&project.root)
         .output()
-        .expect("failed to execute process");
+        .context("Unable to run rojo")?;
+    if !build.status.success() {
+        anyhow::bail!(
+            "rojo could not build {} from {} ({}): {}",
+            target.name,
+            target.project_file,
+            build.status,
+            String::from_utf8_lossy(&build.stderr).trim()
+        );
+    }
 
-    Some(output)
+    run_hook(project, hooks, Hook::PostBuild, &context, out)?;
+    Ok(output_path.to_string_lossy().to_string())
 }
@@ -0,0 +1,234 @@
+use crate::color::Color;
+use crate::config::{
+    read_merged, schema, validate, ConfigData, Document, Issue, Issues, Project, Reader,
+};
+use anyhow::Context;
+use clap::{Args, Subcommand};
+use serde_json::{json, Value};
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommands {
+    /// Check the config file for mistakes
+    Validate,
+
+    /// Print a JSON Schema for the config file
+    Schema,
+
+    /// Print the config, merged with the local config file if there is one
+    Show {
+        /// List every value with the file it came from
+        #[clap(long)]
+        resolved: bool,
+    },
+
+    /// Print the value of a key, such as deployment.universes.main
+    Get {
+        /// The dotted path of the key
+        key: String,
+    },
+
+    /// Set the value of a key, creating it if needed
+    Set {
+        /// The dotted path of the key
+        key: String,
+
+        /// The new value, parsed as JSON when possible
+        value: String,
+    },
+
+    /// Add a deployment branch
+    AddBranch {
+        /// The name of the branch
+        branch: String,
+
+        /// The universe id of the branch
+        #[clap(short, long, value_parser)]
+        universe: u64,
+
+        /// A branch to inherit places from
+        #[clap(short, long, value_parser)]
+        extends: Option<String>,
+    },
+
+    /// Add a place to a deployment branch
+    AddPlace {
+        /// The branch to add the place to
+        branch: String,
+
+        /// The name of the place, which is also the name of its project file
+        name: String,
+
+        /// The place id
+        id: u64,
+    },
+}
+
+/// Manage the config file
+#[derive(Debug, Args)]
+pub struct ConfigCommand {
+    #[clap(subcommand)]
+    command: ConfigCommands,
+}
+
+impl ConfigCommand {
+    pub fn run(self, project: &Project) -> anyhow::Result<Option<String>> {
+        match self.command {
+            ConfigCommands::Validate => {
+                let path = project.config_path()?.display().to_string();
+                let merged = read_merged(&path)?;
+                let label = merged.label(&path);
+
+                // A variable used only by one branch may be in that branch's env file, which is
+                // loaded when the branch is used, so only variables every branch uses must be set
+                let mut reader = Reader::new();
+                ConfigData::read(&mut reader, &merged.json);
+                let mut issues: Vec<Issue> = reader
+                    .issues
+                    .into_iter()
+                    .filter(|issue| issue.branch.is_none())
+                    .collect();
+
+                // The checks across values take placeholders to be valid, so a value that could
+                // not be expanded above is not also reported as missing
+                let data = ConfigData::read(&mut Reader::without_placeholders(), &merged.json);
+                issues.extend(validate(&data, &project.root));
+
+                if !issues.is_empty() {
+                    return Err(Issues {
+                        path: label,
+                        issues,
+                    }
+                    .into());
+                }
+
+                println!("{} {}", Color::green().pad("Validated"), label);
+                Ok(None)
+            }
+
+            ConfigCommands::Schema => Ok(Some(serde_json::to_string_pretty(&schema())?)),
+
+            ConfigCommands::Show { resolved } => {
+                let path = project.config_path()?.display().to_string();
+                let merged = read_merged(&path)?;
+                if !resolved {
+                    return Ok(Some(serde_json::to_string_pretty(&merged.json)?));
+                }
+
+                let mut leaves = Vec::new();
+                collect_leaves(&merged.json, String::new(), &mut leaves);
+
+                let lines: Vec<String> = leaves
+                    .into_iter()
+                    .map(|(pointer, value)| {
+                        let key = pointer[1..]
+                            .replace('/', ".")
+                            .replace("~1", "/")
+                            .replace("~0", "~");
+                        let source = merged.source(&path, &pointer);
+                        let file = std::path::Path::new(source)
+                            .file_name()
+                            .map(|f| f.to_string_lossy().to_string())
+                            .unwrap_or_else(|| source.to_string());
+                        format!("{key} = {value}  ({file})")
+                    })
+                    .collect();
+                Ok(Some(lines.join("\n")))
+            }
+
+            ConfigCommands::Get { key } => {
+                let path = project.config_path()?.display().to_string();
+                let document = Document::open(&path)?;
+
+                match document.get(&key_path(&key))? {
+                    Some(value) => Ok(Some(serde_json::to_string_pretty(&value)?)),
+                    None => Err(anyhow::anyhow!("{} is not set in {}", key, path)),
+                }
+            }
+
+            ConfigCommands::Set { key, value } => {
+                let value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+                update(project, |document| {
+                    document.set(&key_path(&key), value)?;
+                    Ok(key.clone())
+                })
+            }
+
+            ConfigCommands::AddBranch {
+                branch,
+                universe,
+                extends,
+            } => update(project, |document| {
+                let universe_key = ["deployment", "universes", branch.as_str()];
+                if document.get(&universe_key)?.is_some() {
+                    anyhow::bail!("Branch {} already exists", branch);
+                }
+                document.set(&universe_key, json!(universe))?;
+
+                let places_key = ["deployment", "places", branch.as_str()];
+                if document.get(&places_key)?.is_none() {
+                    let places = match extends {
+                        Some(parent) => json!({ "extends": parent }),
+                        None => json!({}),
+                    };
+                    document.set(&places_key, places)?;
+                }
+
+                Ok(format!("branch {branch}"))
+            }),
+
+            ConfigCommands::AddPlace { branch, name, id } => update(project, |document| {
+                if document.get(&["deployment", "places", &branch])?.is_none() {
+                    anyhow::bail!(
+                        "Branch {} does not exist; add it with `rit config add-branch`",
+                        branch
+                    );
+                }
+
+                let place_key = ["deployment", "places", branch.as_str(), name.as_str()];
+                if document.get(&place_key)?.is_some() {
+                    anyhow::bail!(
+                        "Place {} already exists on branch {}; change it with `rit config set`",
+                        name,
+                        branch
+                    );
+                }
+                document.set(&place_key, json!(id))?;
+
+                Ok(place_key.join("."))
+            }),
+        }
+    }
+}
+
+fn key_path(key: &str) -> Vec<&str> {
+    key.split('.').collect()
+}
+
+/// Every value that is not an object, keyed by its JSON pointer
+fn collect_leaves<'a>(json: &'a Value, pointer: String, leaves: &mut Vec<(String, &'a Value)>) {
+    match json {
+        Value::Object(object) if !object.is_empty() => {
+            for (key, value) in object {
+                let escaped = key.replace('~', "~0").replace('/', "~1");
+                collect_leaves(value, format!("{pointer}/{escaped}"), leaves);
+            }
+        }
+        _ if !pointer.is_empty() => leaves.push((pointer, json)),
+        _ => {}
+    }
+}
+
+/// Edits the config file in place, leaving the rest of the file as it was
+fn update(
+    project: &Project,
+    edit: impl FnOnce(&mut Document) -> anyhow::Result<String>,
+) -> anyhow::Result<Option<String>> {
+    let path = project.config_path()?.display().to_string();
+    let mut document = Document::open(&path)?;
+
+    let changed = edit(&mut document).with_context(|| format!("Unable to update {path}"))?;
+    document.save(&path)?;
+
+    println!("{} {} in {}", Color::green().pad("Updated"), changed, path);
+    Ok(None)
+}
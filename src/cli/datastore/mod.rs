@@ -1,7 +1,10 @@
 mod format;
 
 use super::getenv;
-use crate::config::Config;
+use super::output::Output;
+use crate::config::{Config, Project};
+use crate::rbx::{with_rate_limit_retry, with_retry};
+use anyhow::Context;
 use clap::{Args, Subcommand, ValueEnum};
 
 use std::io::{stdin, stdout, Write};
@@ -17,7 +20,7 @@ use rbxcloud::rbx::{
     RbxCloud, ReturnLimit, RobloxUserId, UniverseId,
 };
 
-#[derive(Debug, Subcommand)]
+#[derive(Debug, Clone, Subcommand)]
 pub enum DataStoreCommands {
     /// List all DataStores in a given universe
     ListStores {
@@ -25,6 +28,10 @@ pub enum DataStoreCommands {
         #[clap(short, long, value_parser)]
         branch_name: Option<String>,
 
+        /// The DataStore alias from the config to use as the prefix, in place of --prefix
+        #[clap(long, value_parser, conflicts_with = "prefix")]
+        store: Option<String>,
+
         /// Return only DataStores with this prefix
         #[clap(short, long, value_parser)]
         prefix: Option<String>,
@@ -48,6 +55,10 @@ pub enum DataStoreCommands {
         #[clap(short, long, value_parser)]
         branch_name: Option<String>,
 
+        /// The DataStore alias from the config to use
+        #[clap(long, value_parser)]
+        store: Option<String>,
+
         /// DataStore name
         #[clap(short, long, value_parser)]
         datastore_name: Option<String>,
@@ -83,6 +94,10 @@ pub enum DataStoreCommands {
         #[clap(short, long, value_parser)]
         branch_name: Option<String>,
 
+        /// The DataStore alias from the config to use
+        #[clap(long, value_parser)]
+        store: Option<String>,
+
         /// DataStore name
         #[clap(short, long, value_parser)]
         datastore_name: Option<String>,
@@ -106,6 +121,10 @@ pub enum DataStoreCommands {
         #[clap(short, long, value_parser)]
         branch_name: Option<String>,
 
+        /// The DataStore alias from the config to use
+        #[clap(long, value_parser)]
+        store: Option<String>,
+
         /// DataStore name
         #[clap(short, long, value_parser)]
         datastore_name: Option<String>,
@@ -149,6 +168,10 @@ pub enum DataStoreCommands {
         #[clap(short, long, value_parser)]
         branch_name: Option<String>,
 
+        /// The DataStore alias from the config to use
+        #[clap(long, value_parser)]
+        store: Option<String>,
+
         /// DataStore name
         #[clap(short, long, value_parser)]
         datastore_name: Option<String>,
@@ -184,6 +207,10 @@ pub enum DataStoreCommands {
         #[clap(short, long, value_parser)]
         branch_name: Option<String>,
 
+        /// The DataStore alias from the config to use
+        #[clap(long, value_parser)]
+        store: Option<String>,
+
         /// DataStore name
         #[clap(short, long, value_parser)]
         datastore_name: Option<String>,
@@ -207,6 +234,10 @@ pub enum DataStoreCommands {
         #[clap(short, long, value_parser)]
         branch_name: Option<String>,
 
+        /// The DataStore alias from the config to use
+        #[clap(long, value_parser)]
+        store: Option<String>,
+
         /// DataStore name
         #[clap(short, long, value_parser)]
         datastore_name: Option<String>,
@@ -250,6 +281,10 @@ pub enum DataStoreCommands {
         #[clap(short, long, value_parser)]
         branch_name: Option<String>,
 
+        /// The DataStore alias from the config to use
+        #[clap(long, value_parser)]
+        store: Option<String>,
+
         /// DataStore name
         #[clap(short, long, value_parser)]
         datastore_name: Option<String>,
@@ -279,7 +314,7 @@ pub enum ListEntrySortOrder {
 }
 
 /// Manage the datastore
-#[derive(Debug, Args)]
+#[derive(Debug, Clone, Args)]
 pub struct DataStore {
     #[clap(subcommand)]
     command: DataStoreCommands,
@@ -294,53 +329,89 @@ fn u64_ids_to_roblox_ids(user_ids: Option<Vec<u64>>) -> Option<Vec<RobloxUserId>
     })
 }
 
-fn get_config(branch_name: Option<String>) -> Config {
-    let branch = match branch_name {
-        Some(v) => v,
-        None => "main".to_string(),
-    };
+fn get_config(project: &Project, branch_name: Option<String>) -> anyhow::Result<Config> {
+    Config::for_branch(project, branch_name)
+}
 
-    Config::new(branch)
+fn universe_id(config: &Config) -> anyhow::Result<UniverseId> {
+    Ok(UniverseId(config.get_universe_id()?))
 }
 
-fn universe_id(config: Config) -> UniverseId {
-    UniverseId(config.get_universe_id().unwrap())
+/// Resolves the DataStore name and scope, falling back to the selected store or the config
+fn datastore_target(
+    config: &Config,
+    store: Option<String>,
+    datastore_name: Option<String>,
+    scope: Option<String>,
+) -> anyhow::Result<(String, Option<String>)> {
+    let (config_name, config_scope) = match store {
+        Some(alias) => {
+            let (name, scope) = config.get_store(&alias)?;
+            (Some(name), Some(scope))
+        }
+        None => config.get_datastore(),
+    };
+
+    let name = datastore_name.or(config_name).with_context(|| {
+        format!(
+            "No DataStore name given; pass --datastore-name or set datastore.name in {}",
+            config.path
+        )
+    })?;
+    let scope = scope.or(config_scope).with_context(|| {
+        format!(
+            "No DataStore scope given; pass --scope or set datastore.scope in {}",
+            config.path
+        )
+    })?;
+
+    Ok((name, Some(scope)))
 }
 
 impl DataStore {
-    pub async fn run(self) -> anyhow::Result<Option<String>> {
+    pub async fn run(self, project: &Project) -> anyhow::Result<Option<String>> {
         match self.command {
             DataStoreCommands::ListStores {
                 branch_name,
+                store,
                 prefix,
                 limit,
                 cursor,
                 api_key,
             } => {
-                let auth: String = getenv(api_key, "OPENCLOUD_KEY".to_string());
-                let config = get_config(branch_name);
-
-                let rbx_cloud = RbxCloud::new(&auth, universe_id(config));
+                let config = get_config(project, branch_name)?;
+                let auth = getenv(api_key, &config.api_key_env()?, Some(&config.branch))?;
+                let prefix = match store {
+                    Some(alias) => Some(config.get_store(&alias)?.0),
+                    None => prefix,
+                };
+
+                let rbx_cloud = RbxCloud::new(&auth, universe_id(&config)?);
                 let datastore = rbx_cloud.datastore();
 
                 let mut has_cursor = true;
                 let mut next_cursor = cursor.clone();
 
                 while has_cursor {
-                    let res = datastore
-                        .list_stores(&DataStoreListStores {
-                            cursor: next_cursor,
-                            limit: ReturnLimit(limit.unwrap_or(100)),
-                            prefix: prefix.clone(),
-                        })
-                        .await;
+                    let params = DataStoreListStores {
+                        cursor: next_cursor,
+                        limit: ReturnLimit(limit.unwrap_or(100)),
+                        prefix: prefix.clone(),
+                    };
+                    let res = with_retry(
+                        &config.data.retry,
+                        "listing of DataStores",
+                        &Output::default(),
+                        || datastore.list_stores(&params),
+                    )
+                    .await;
                     match res {
                         Ok(data) => {
                             has_cursor = data.next_page_cursor.clone() != Some("".to_string());
                             next_cursor = data.next_page_cursor.clone();
                             println!("{}", format_datastore_list_store(data));
                         }
-                        Err(err) => return Err(err.into()),
+                        Err(err) => return Err(err),
                     }
                     if !has_cursor {
                         break;
@@ -363,6 +434,7 @@ impl DataStore {
 
             DataStoreCommands::List {
                 branch_name,
+                store,
                 prefix,
                 limit,
                 cursor,
@@ -371,37 +443,39 @@ impl DataStore {
                 scope,
                 all_scopes,
             } => {
-                let auth: String = getenv(api_key, "OPENCLOUD_KEY".to_string());
-                let config = get_config(branch_name);
-                let config_datastore = config.get_datastore();
+                let config = get_config(project, branch_name)?;
+                let auth = getenv(api_key, &config.api_key_env()?, Some(&config.branch))?;
+                let (name, scope) = datastore_target(&config, store, datastore_name, scope)?;
 
-                let name = datastore_name.unwrap_or(config_datastore.0.unwrap());
-                let scope = Some(scope.unwrap_or(config_datastore.1.unwrap()));
-
-                let rbx_cloud = RbxCloud::new(&auth, universe_id(config));
+                let rbx_cloud = RbxCloud::new(&auth, universe_id(&config)?);
                 let datastore = rbx_cloud.datastore();
 
                 let mut has_cursor = true;
                 let mut next_cursor = cursor.clone();
 
                 while has_cursor {
-                    let res = datastore
-                        .list_entries(&DataStoreListEntries {
-                            name: name.clone(),
-                            scope: scope.clone(),
-                            all_scopes,
-                            cursor: next_cursor,
-                            limit: ReturnLimit(limit.unwrap_or(100)),
-                            prefix: prefix.clone(),
-                        })
-                        .await;
+                    let params = DataStoreListEntries {
+                        name: name.clone(),
+                        scope: scope.clone(),
+                        all_scopes,
+                        cursor: next_cursor,
+                        limit: ReturnLimit(limit.unwrap_or(100)),
+                        prefix: prefix.clone(),
+                    };
+                    let res = with_retry(
+                        &config.data.retry,
+                        &format!("listing of entries in {name}"),
+                        &Output::default(),
+                        || datastore.list_entries(&params),
+                    )
+                    .await;
                     match res {
                         Ok(data) => {
                             has_cursor = data.next_page_cursor.clone() != Some("".to_string());
                             next_cursor = data.next_page_cursor.clone();
                             println!("{}", format_datastore_list_entry(data));
                         }
-                        Err(err) => return Err(err.into()),
+                        Err(err) => return Err(err),
                     }
                     if !has_cursor {
                         break;
@@ -424,31 +498,35 @@ impl DataStore {
 
             DataStoreCommands::Get {
                 branch_name,
+                store,
                 datastore_name,
                 scope,
                 key,
                 api_key,
             } => {
-                let auth = getenv(api_key, "OPENCLOUD_KEY".to_string());
-                let config = get_config(branch_name);
-                let config_datastore = config.get_datastore();
+                let config = get_config(project, branch_name)?;
+                let auth = getenv(api_key, &config.api_key_env()?, Some(&config.branch))?;
+                let (name, scope) = datastore_target(&config, store, datastore_name, scope)?;
 
-                let name = datastore_name.unwrap_or(config_datastore.0.unwrap());
-                let scope = Some(scope.unwrap_or(config_datastore.1.unwrap()));
-
-                let rbx_cloud = RbxCloud::new(&auth, universe_id(config));
+                let rbx_cloud = RbxCloud::new(&auth, universe_id(&config)?);
                 let datastore = rbx_cloud.datastore();
-                let res = datastore
-                    .get_entry_string(&DataStoreGetEntry { name, scope, key })
-                    .await;
+                let params = DataStoreGetEntry { name, scope, key };
+                let res = with_retry(
+                    &config.data.retry,
+                    &format!("read of {}", params.key),
+                    &Output::default(),
+                    || datastore.get_entry_string(&params),
+                )
+                .await;
                 match res {
                     Ok(data) => Ok(Some(format_json(data))),
-                    Err(err) => Err(err.into()),
+                    Err(err) => Err(err),
                 }
             }
 
             DataStoreCommands::Set {
                 branch_name,
+                store,
                 datastore_name,
                 scope,
                 key,
@@ -459,36 +537,39 @@ impl DataStore {
                 attributes,
                 api_key,
             } => {
-                let auth = getenv(api_key, "OPENCLOUD_KEY".to_string());
-                let config = get_config(branch_name);
-                let config_datastore = config.get_datastore();
+                let config = get_config(project, branch_name)?;
+                let auth = getenv(api_key, &config.api_key_env()?, Some(&config.branch))?;
+                let (name, scope) = datastore_target(&config, store, datastore_name, scope)?;
 
-                let name = datastore_name.unwrap_or(config_datastore.0.unwrap());
-                let scope = Some(scope.unwrap_or(config_datastore.1.unwrap()));
-
-                let rbx_cloud = RbxCloud::new(&auth, universe_id(config));
+                let rbx_cloud = RbxCloud::new(&auth, universe_id(&config)?);
                 let datastore = rbx_cloud.datastore();
                 let ids = u64_ids_to_roblox_ids(user_ids);
-                let res = datastore
-                    .set_entry(&DataStoreSetEntry {
-                        name,
-                        scope,
-                        key,
-                        match_version,
-                        exclusive_create,
-                        roblox_entry_user_ids: ids,
-                        roblox_entry_attributes: attributes,
-                        data,
-                    })
-                    .await;
+                let params = DataStoreSetEntry {
+                    name,
+                    scope,
+                    key,
+                    match_version,
+                    exclusive_create,
+                    roblox_entry_user_ids: ids,
+                    roblox_entry_attributes: attributes,
+                    data,
+                };
+                let res = with_retry(
+                    &config.data.retry,
+                    &format!("write of {}", params.key),
+                    &Output::default(),
+                    || datastore.set_entry(&params),
+                )
+                .await;
                 match res {
                     Ok(data) => Ok(Some(format!("{data:#?}"))),
-                    Err(err) => Err(err.into()),
+                    Err(err) => Err(err),
                 }
             }
 
             DataStoreCommands::Increment {
                 branch_name,
+                store,
                 datastore_name,
                 scope,
                 key,
@@ -497,59 +578,65 @@ impl DataStore {
                 attributes,
                 api_key,
             } => {
-                let auth = getenv(api_key, "OPENCLOUD_KEY".to_string());
-                let config = get_config(branch_name);
-                let config_datastore = config.get_datastore();
-
-                let name = datastore_name.unwrap_or(config_datastore.0.unwrap());
-                let scope = Some(scope.unwrap_or(config_datastore.1.unwrap()));
+                let config = get_config(project, branch_name)?;
+                let auth = getenv(api_key, &config.api_key_env()?, Some(&config.branch))?;
+                let (name, scope) = datastore_target(&config, store, datastore_name, scope)?;
 
-                let rbx_cloud = RbxCloud::new(&auth, universe_id(config));
+                let rbx_cloud = RbxCloud::new(&auth, universe_id(&config)?);
                 let datastore = rbx_cloud.datastore();
                 let ids = u64_ids_to_roblox_ids(user_ids);
-                let res = datastore
-                    .increment_entry(&DataStoreIncrementEntry {
-                        name,
-                        scope,
-                        key,
-                        roblox_entry_user_ids: ids,
-                        roblox_entry_attributes: attributes,
-                        increment_by,
-                    })
-                    .await;
+                let params = DataStoreIncrementEntry {
+                    name,
+                    scope,
+                    key,
+                    roblox_entry_user_ids: ids,
+                    roblox_entry_attributes: attributes,
+                    increment_by,
+                };
+                let res = with_rate_limit_retry(
+                    &config.data.retry,
+                    &format!("increment of {}", params.key),
+                    &Output::default(),
+                    || datastore.increment_entry(&params),
+                )
+                .await;
                 match res {
                     Ok(data) => Ok(Some(format!("{data}"))),
-                    Err(err) => Err(err.into()),
+                    Err(err) => Err(err),
                 }
             }
 
             DataStoreCommands::Delete {
                 branch_name,
+                store,
                 datastore_name,
                 scope,
                 key,
                 api_key,
             } => {
-                let auth = getenv(api_key, "OPENCLOUD_KEY".to_string());
-                let config = get_config(branch_name);
-                let config_datastore = config.get_datastore();
-
-                let name = datastore_name.unwrap_or(config_datastore.0.unwrap());
-                let scope = Some(scope.unwrap_or(config_datastore.1.unwrap()));
+                let config = get_config(project, branch_name)?;
+                let auth = getenv(api_key, &config.api_key_env()?, Some(&config.branch))?;
+                let (name, scope) = datastore_target(&config, store, datastore_name, scope)?;
 
-                let rbx_cloud = RbxCloud::new(&auth, universe_id(config));
+                let rbx_cloud = RbxCloud::new(&auth, universe_id(&config)?);
                 let datastore = rbx_cloud.datastore();
-                let res = datastore
-                    .delete_entry(&DataStoreDeleteEntry { name, scope, key })
-                    .await;
+                let params = DataStoreDeleteEntry { name, scope, key };
+                let res = with_retry(
+                    &config.data.retry,
+                    &format!("delete of {}", params.key),
+                    &Output::default(),
+                    || datastore.delete_entry(&params),
+                )
+                .await;
                 match res {
                     Ok(_) => Ok(None),
-                    Err(err) => Err(err.into()),
+                    Err(err) => Err(err),
                 }
             }
 
             DataStoreCommands::ListVersions {
                 branch_name,
+                store,
                 datastore_name,
                 scope,
                 key,
@@ -560,14 +647,11 @@ impl DataStore {
                 cursor,
                 api_key,
             } => {
-                let auth = getenv(api_key, "OPENCLOUD_KEY".to_string());
-                let config = get_config(branch_name);
-                let config_datastore = config.get_datastore();
-
-                let name = datastore_name.unwrap_or(config_datastore.0.unwrap());
-                let scope = Some(scope.unwrap_or(config_datastore.1.unwrap()));
+                let config = get_config(project, branch_name)?;
+                let auth = getenv(api_key, &config.api_key_env()?, Some(&config.branch))?;
+                let (name, scope) = datastore_target(&config, store, datastore_name, scope)?;
 
-                let rbx_cloud = RbxCloud::new(&auth, universe_id(config));
+                let rbx_cloud = RbxCloud::new(&auth, universe_id(&config)?);
                 let datastore = rbx_cloud.datastore();
 
                 let mut has_cursor = true;
@@ -575,25 +659,30 @@ impl DataStore {
                 let order = sort_order.unwrap_or(ListEntrySortOrder::Ascending);
 
                 while has_cursor {
-                    let res = datastore
-                        .list_entry_versions(&DataStoreListEntryVersions {
-                            name: name.clone(),
-                            scope: scope.clone(),
-                            key: key.clone(),
-                            start_time: start_time.clone(),
-                            end_time: end_time.clone(),
-                            sort_order: format!("{order:?}"),
-                            limit: ReturnLimit(limit.unwrap_or(100)),
-                            cursor: next_cursor,
-                        })
-                        .await;
+                    let params = DataStoreListEntryVersions {
+                        name: name.clone(),
+                        scope: scope.clone(),
+                        key: key.clone(),
+                        start_time: start_time.clone(),
+                        end_time: end_time.clone(),
+                        sort_order: format!("{order:?}"),
+                        limit: ReturnLimit(limit.unwrap_or(100)),
+                        cursor: next_cursor,
+                    };
+                    let res = with_retry(
+                        &config.data.retry,
+                        &format!("listing of versions of {key}"),
+                        &Output::default(),
+                        || datastore.list_entry_versions(&params),
+                    )
+                    .await;
                     match res {
                         Ok(data) => {
                             has_cursor = data.next_page_cursor.clone() != Some("".to_string());
                             next_cursor = data.next_page_cursor.clone();
                             println!("{}", format_datastore_list_entry_version(data));
                         }
-                        Err(err) => return Err(err.into()),
+                        Err(err) => return Err(err),
                     }
                     if !has_cursor {
                         break;
@@ -616,32 +705,35 @@ impl DataStore {
 
             DataStoreCommands::GetVersion {
                 branch_name,
+                store,
                 datastore_name,
                 scope,
                 key,
                 version_id,
                 api_key,
             } => {
-                let auth = getenv(api_key, "OPENCLOUD_KEY".to_string());
-                let config = get_config(branch_name);
-                let config_datastore = config.get_datastore();
-
-                let name = datastore_name.unwrap_or(config_datastore.0.unwrap());
-                let scope = Some(scope.unwrap_or(config_datastore.1.unwrap()));
+                let config = get_config(project, branch_name)?;
+                let auth = getenv(api_key, &config.api_key_env()?, Some(&config.branch))?;
+                let (name, scope) = datastore_target(&config, store, datastore_name, scope)?;
 
-                let rbx_cloud = RbxCloud::new(&auth, universe_id(config));
+                let rbx_cloud = RbxCloud::new(&auth, universe_id(&config)?);
                 let datastore = rbx_cloud.datastore();
-                let res = datastore
-                    .get_entry_version(&DataStoreGetEntryVersion {
-                        name,
-                        scope,
-                        key,
-                        version_id,
-                    })
-                    .await;
+                let params = DataStoreGetEntryVersion {
+                    name,
+                    scope,
+                    key,
+                    version_id,
+                };
+                let res = with_retry(
+                    &config.data.retry,
+                    &format!("read of {} version {}", params.key, params.version_id),
+                    &Output::default(),
+                    || datastore.get_entry_version(&params),
+                )
+                .await;
                 match res {
                     Ok(data) => Ok(Some(format_json(data))),
-                    Err(err) => Err(err.into()),
+                    Err(err) => Err(err),
                 }
             }
         }
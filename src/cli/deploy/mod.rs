@@ -0,0 +1,462 @@
+mod plan;
+
+use super::build::build_place;
+use super::getenv;
+use super::hooks::{run_hook, HookContext};
+use super::output::Output;
+use super::table::table;
+use crate::color::Color;
+use crate::config::{current_commit, Config, Hook, Hooks, OutputFormat, Project, VersionType};
+use crate::history::{
+    fingerprint, new_deploy_id, sha256, timestamp, Artifacts, DeployRecord, History, ARTIFACTS_DIR,
+};
+use crate::rbx::{Message, Universe};
+use clap::Parser;
+use plan::{DeployPlan, PlaceFilter, PlannedPlace};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Build all projects and deploy them to Roblox
+#[derive(Debug, Clone, Parser)]
+pub struct DeployCommand {
+    /// The branch to deploy to
+    #[clap(short, long, value_parser)]
+    branch_name: Option<String>,
+    /// The deploy message
+    #[clap(short, long, value_parser)]
+    message: Option<String>,
+    /// The Roblox API key
+    #[clap(short, long, value_parser)]
+    api_key: Option<String>,
+    /// Publish as a saved version or a live one, overriding the config
+    #[clap(long, value_enum)]
+    version_type: Option<VersionType>,
+    /// Deploy only this place; can be given more than once
+    #[clap(long = "place", value_parser)]
+    places: Vec<String>,
+    /// Leave this place out of the deploy; can be given more than once
+    #[clap(long, value_parser)]
+    exclude: Vec<String>,
+    /// Show what would be built and published without doing it
+    #[clap(long)]
+    plan: bool,
+    /// Print the plan as JSON
+    #[clap(long, requires = "plan")]
+    json: bool,
+    /// How many places to build and publish at once
+    #[clap(short, long, value_parser, default_value_t = 1)]
+    jobs: usize,
+    /// Start no more places once one fails (the default)
+    #[clap(long, conflicts_with = "keep-going")]
+    fail_fast: bool,
+    /// Build and publish every place even after one fails
+    #[clap(long)]
+    keep_going: bool,
+    /// Publish places whose build has not changed since their last publish
+    #[clap(long)]
+    force: bool,
+}
+
+impl DeployCommand {
+    pub async fn run(self, project: &Project) -> anyhow::Result<Option<String>> {
+        let config = Config::for_branch(project, self.branch_name.clone())?;
+        let filter = PlaceFilter {
+            only: self.places.clone(),
+            exclude: self.exclude.clone(),
+        };
+        let plan = DeployPlan::new(
+            project,
+            &config,
+            &filter,
+            self.message.clone(),
+            self.version_type,
+        )?;
+        if self.plan {
+            return Ok(Some(if self.json {
+                serde_json::to_string_pretty(&plan)?
+            } else {
+                plan.to_table()
+            }));
+        }
+
+        let branch = config.branch.clone();
+        let api_key = getenv(self.api_key.clone(), &plan.api_key_env, Some(&branch))?;
+        let hooks = &config.data.hooks;
+        let context = HookContext {
+            branch: Some(branch.clone()),
+            ..HookContext::default()
+        };
+
+        run_hook(
+            project,
+            hooks,
+            Hook::PreDeploy,
+            &context,
+            &Output::default(),
+        )?;
+
+        println!(
+            "{} to {} universe",
+            Color::green().pad("Publishing"),
+            branch.clone()
+        );
+
+        let universe_id = plan.universe_id;
+        let retry = config.data.retry;
+        let universe = Arc::new(Universe::new(&api_key, universe_id, retry));
+        let jobs = self.jobs.max(1);
+        let semaphore = Arc::new(Semaphore::new(jobs));
+        let stop = Arc::new(AtomicBool::new(false));
+        let fail_fast = !self.keep_going;
+        let width = plan.places.iter().map(|p| p.name.len() + 2).max();
+        let history = History::new(project);
+        let artifacts = Artifacts::new(project);
+        let deploy = DeployInfo {
+            id: new_deploy_id(),
+            branch: branch.clone(),
+            universe_id,
+            commit: current_commit(&project.root),
+            message: self.message.clone(),
+        };
+
+        // The last publish of each place on this branch, which an unchanged build can be skipped for
+        let mut last_published: HashMap<String, DeployRecord> = HashMap::new();
+        if !self.force {
+            for record in history.read()? {
+                if record.branch == branch {
+                    last_published.insert(record.place.clone(), record);
+                }
+            }
+        }
+
+        let mut running = JoinSet::new();
+        for (index, place) in plan.places.iter().enumerate() {
+            if place.skip {
+                println!("{} {}", Color::blue().pad("Skipping"), place.name);
+                continue;
+            }
+
+            let job = PlaceJob {
+                project: project.clone(),
+                hooks: hooks.clone(),
+                context: context.clone(),
+                universe: universe.clone(),
+                place: place.clone(),
+                history: history.clone(),
+                artifacts: artifacts.clone(),
+                deploy: deploy.clone(),
+                last_published: last_published.remove(&place.name),
+                out: match (jobs > 1, width) {
+                    (true, Some(width)) => Output::for_place(&place.name, width),
+                    _ => Output::default(),
+                },
+            };
+            let (semaphore, stop) = (semaphore.clone(), stop.clone());
+            running.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                if stop.load(Ordering::SeqCst) {
+                    return (index, PlaceOutcome::Cancelled, Duration::ZERO);
+                }
+
+                let started = Instant::now();
+                let (name, out) = (job.place.name.clone(), job.out.clone());
+                let outcome = match job.run().await {
+                    Ok(outcome) => outcome,
+                    Err(err) => {
+                        out.error(format!(
+                            "{} {}: {:#}",
+                            Color::red().pad("Failed"),
+                            name,
+                            err
+                        ));
+                        if fail_fast {
+                            stop.store(true, Ordering::SeqCst);
+                        }
+                        PlaceOutcome::Failed
+                    }
+                };
+                (index, outcome, started.elapsed())
+            });
+        }
+
+        let mut results = Vec::new();
+        while let Some(joined) = running.join_next().await {
+            results.push(joined?);
+        }
+        results.sort_by_key(|(index, ..)| *index);
+
+        println!("\n{}", summary(&plan, &results));
+        prune_artifacts(&history, &artifacts, &config);
+
+        // A failed place stops the deploy before the message is sent, like a failing pre hook
+        let failed: Vec<&str> = results
+            .iter()
+            .filter(|(_, outcome, _)| {
+                matches!(outcome, PlaceOutcome::Failed | PlaceOutcome::Cancelled)
+            })
+            .map(|(index, ..)| plan.places[*index].name.as_str())
+            .collect();
+        if !failed.is_empty() {
+            anyhow::bail!(
+                "Deploy to {} did not finish; {} of {} places were not published: {}",
+                branch,
+                failed.len(),
+                results.len(),
+                failed.join(", ")
+            );
+        }
+
+        if let Some(message) = &plan.message {
+            Message::new(&api_key, universe_id, retry)
+                .publish(&message.topic, &message.text)
+                .await?;
+        }
+
+        run_hook(
+            project,
+            hooks,
+            Hook::PostDeploy,
+            &context,
+            &Output::default(),
+        )?;
+        Ok(None)
+    }
+}
+
+/// How a place fared in a deploy
+enum PlaceOutcome {
+    Published(u64),
+    /// Built the same as its last publish, which has this version
+    Unchanged(u64),
+    Failed,
+    /// Never started, because another place failed first
+    Cancelled,
+}
+
+/// What the history records of every place in a deploy have in common
+#[derive(Debug, Clone)]
+struct DeployInfo {
+    id: String,
+    branch: String,
+    universe_id: u64,
+    commit: Option<String>,
+    message: Option<String>,
+}
+
+/// The build and publish of one place, run alongside the other places of the deploy
+struct PlaceJob {
+    project: Project,
+    hooks: Hooks,
+    context: HookContext,
+    universe: Arc<Universe>,
+    place: PlannedPlace,
+    history: History,
+    artifacts: Artifacts,
+    deploy: DeployInfo,
+    last_published: Option<DeployRecord>,
+    out: Output,
+}
+
+impl PlaceJob {
+    /// Builds the place and publishes it unless the build matches its last publish
+    async fn run(self) -> anyhow::Result<PlaceOutcome> {
+        let place = &self.place;
+        let context = HookContext {
+            place_id: Some(place.place_id),
+            ..self.context.clone()
+        };
+
+        // Builds and hooks block on other processes, so they run off the async workers
+        let path = {
+            let (project, hooks, context, out) = (
+                self.project.clone(),
+                self.hooks.clone(),
+                context.clone(),
+                self.out.clone(),
+            );
+            let target = place.target();
+            tokio::task::spawn_blocking(move || {
+                build_place(&project, &target, &hooks, &context, &out)
+            })
+            .await??
+        };
+
+        // Parsing a large place takes a while, so it is also kept off the async workers
+        let (bytes, content_hash) = {
+            let path = PathBuf::from(&path);
+            tokio::task::spawn_blocking(move || {
+                let bytes = fs_err::read(&path)?;
+                let content_hash = fingerprint(&path, &bytes);
+                anyhow::Ok((bytes, content_hash))
+            })
+            .await??
+        };
+        if let Some(last) = self.unchanged_since(&content_hash) {
+            self.out.status(
+                Color::blue(),
+                "Unchanged",
+                format!(
+                    "{} ({}) is the same as version {} from deploy {}; pass --force to publish it anyway",
+                    place.name, place.place_id, last.version, last.deploy_id
+                ),
+            );
+            return Ok(PlaceOutcome::Unchanged(last.version));
+        }
+
+        let format = OutputFormat::from_path(Path::new(&path));
+        let version = self
+            .universe
+            .publish(
+                &self.out,
+                &place.name,
+                &path,
+                format,
+                place.place_id,
+                place.version_type,
+            )
+            .await?;
+        self.out.status(
+            Color::green(),
+            match place.version_type {
+                VersionType::Saved => "Saved",
+                VersionType::Published => "Published",
+            },
+            format!(
+                "{} ({}) with version number: {}",
+                place.name, place.place_id, version
+            ),
+        );
+        self.record(&bytes, format, content_hash, version);
+
+        let context = HookContext {
+            place_name: Some(place.name.clone()),
+            version: Some(version),
+            artifact: Some(path),
+            ..context
+        };
+        let (project, hooks, out) = (self.project, self.hooks, self.out);
+        tokio::task::spawn_blocking(move || {
+            run_hook(&project, &hooks, Hook::PostPublishPlace, &context, &out)
+        })
+        .await??;
+
+        Ok(PlaceOutcome::Published(version))
+    }
+
+    /// The last publish of the place, if it was the same build to the same place the same way
+    fn unchanged_since(&self, content_hash: &str) -> Option<&DeployRecord> {
+        self.last_published.as_ref().filter(|last| {
+            last.content_hash.as_deref() == Some(content_hash)
+                && last.place_id == self.place.place_id
+                && last.universe_id == self.deploy.universe_id
+                && last.version_type == self.place.version_type
+        })
+    }
+}
+
+impl PlaceJob {
+    /// Adds the publish to the deploy history and keeps the build for rollbacks; a failure here
+    /// is reported but does not undo the publish, and a build that cannot be kept still gets
+    /// its history record
+    fn record(&self, bytes: &[u8], output: OutputFormat, content_hash: String, version: u64) {
+        if let Err(err) = self.artifacts.put(bytes) {
+            self.out.error(format!(
+                "{} build of {} in {}, so it cannot be rolled back to: {:#}",
+                Color::red().pad("Unstored"),
+                self.place.name,
+                ARTIFACTS_DIR,
+                err
+            ));
+        }
+
+        let recorded = self.history.append(&DeployRecord {
+            deploy_id: self.deploy.id.clone(),
+            timestamp: timestamp(),
+            branch: self.deploy.branch.clone(),
+            universe_id: self.deploy.universe_id,
+            place: self.place.name.clone(),
+            place_id: self.place.place_id,
+            version,
+            version_type: self.place.version_type,
+            artifact_sha256: sha256(bytes),
+            output,
+            content_hash: Some(content_hash),
+            commit: self.deploy.commit.clone(),
+            message: self.deploy.message.clone(),
+            rollback_of: None,
+        });
+
+        if let Err(err) = recorded {
+            self.out.error(format!(
+                "{} {} in {}: {:#}",
+                Color::red().pad("Unrecorded"),
+                self.place.name,
+                self.history.path().display(),
+                err
+            ));
+        }
+    }
+}
+
+/// Drops the builds that fall outside the retention limits, warning if that fails
+pub fn prune_artifacts(history: &History, artifacts: &Artifacts, config: &Config) {
+    let pruned = history
+        .read()
+        .and_then(|records| artifacts.prune(&records, &config.data.artifacts));
+    if let Err(err) = pruned {
+        eprintln!(
+            "{} old builds from {}: {:#}",
+            Color::red().pad("Unpruned"),
+            ARTIFACTS_DIR,
+            err
+        );
+    }
+}
+
+fn summary(plan: &DeployPlan, results: &[(usize, PlaceOutcome, Duration)]) -> String {
+    let rows: Vec<Vec<String>> = plan
+        .places
+        .iter()
+        .enumerate()
+        .map(|(index, place)| {
+            let result = results.iter().find(|(i, ..)| *i == index);
+            let (status, version, duration) = match result {
+                None => ("skipped", String::new(), String::new()),
+                Some((_, PlaceOutcome::Cancelled, _)) => {
+                    ("cancelled", String::new(), String::new())
+                }
+                Some((_, outcome, duration)) => (
+                    match outcome {
+                        PlaceOutcome::Published(_) => "published",
+                        PlaceOutcome::Unchanged(_) => "unchanged",
+                        _ => "failed",
+                    },
+                    match outcome {
+                        PlaceOutcome::Published(version) | PlaceOutcome::Unchanged(version) => {
+                            version.to_string()
+                        }
+                        _ => String::new(),
+                    },
+                    format!("{:.1}s", duration.as_secs_f64()),
+                ),
+            };
+            vec![
+                place.name.clone(),
+                status.to_string(),
+                format!("{:?}", place.version_type),
+                version,
+                duration,
+            ]
+        })
+        .collect();
+
+    table(
+        &["PLACE", "STATUS", "VERSION TYPE", "VERSION", "DURATION"],
+        &rows,
+    )
+}
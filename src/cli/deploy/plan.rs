@@ -0,0 +1,179 @@
+use crate::cli::build::BuildTarget;
+use crate::cli::table::table;
+use crate::config::{Config, PlaceConfig, Project, VersionType};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// What a deploy will do, resolved from the config without building or publishing anything
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployPlan {
+    pub branch: String,
+    pub universe_id: u64,
+    pub api_key_env: String,
+    pub places: Vec<PlannedPlace>,
+    pub message: Option<PlannedMessage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedPlace {
+    pub name: String,
+    pub place_id: u64,
+    pub project_file: String,
+    pub project_file_exists: bool,
+    pub output: String,
+    pub version_type: VersionType,
+    pub skip: bool,
+}
+
+/// The places picked out of a branch with `--place` and `--exclude`
+#[derive(Debug, Default)]
+pub struct PlaceFilter {
+    /// Deploy only these places, or every place when empty
+    pub only: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlannedMessage {
+    pub topic: String,
+    pub text: String,
+}
+
+impl DeployPlan {
+    pub fn new(
+        project: &Project,
+        config: &Config,
+        filter: &PlaceFilter,
+        message: Option<String>,
+        version_type: Option<VersionType>,
+    ) -> anyhow::Result<Self> {
+        // The flag wins over the place, which wins over the branch
+        let branch_version_type = config.version_type()?;
+        let places = filter
+            .apply(&config.branch, config.get_places()?)?
+            .iter()
+            .map(|(name, place)| {
+                let version_type = version_type
+                    .or(place.version_type)
+                    .unwrap_or(branch_version_type);
+                PlannedPlace::new(project, name, place, version_type)
+            })
+            .collect();
+
+        Ok(DeployPlan {
+            branch: config.branch.clone(),
+            universe_id: config.get_universe_id()?,
+            api_key_env: config.api_key_env()?,
+            places,
+            message: message.map(|text| PlannedMessage {
+                topic: format!("updates-{}", config.branch),
+                text,
+            }),
+        })
+    }
+
+    pub fn to_table(&self) -> String {
+        let rows: Vec<Vec<String>> = self
+            .places
+            .iter()
+            .map(|place| {
+                vec![
+                    place.name.clone(),
+                    place.place_id.to_string(),
+                    if place.project_file_exists {
+                        place.project_file.clone()
+                    } else {
+                        format!("{} (missing)", place.project_file)
+                    },
+                    format!("{:?}", place.version_type),
+                    if place.skip {
+                        "skipped".to_string()
+                    } else {
+                        place.output.clone()
+                    },
+                ]
+            })
+            .collect();
+
+        let mut lines = vec![
+            format!(
+                "Deploy to branch {} (universe {}, key from {})",
+                self.branch, self.universe_id, self.api_key_env
+            ),
+            String::new(),
+            table(
+                &[
+                    "PLACE",
+                    "PLACE ID",
+                    "PROJECT FILE",
+                    "VERSION TYPE",
+                    "OUTPUT",
+                ],
+                &rows,
+            ),
+            String::new(),
+        ];
+        lines.push(match &self.message {
+            Some(message) => format!("Message {:?} to topic {}", message.text, message.topic),
+            None => "No message".to_string(),
+        });
+        lines.join("\n")
+    }
+}
+
+impl PlaceFilter {
+    /// The places that pass the filter, failing on names the branch does not have
+    pub fn apply(
+        &self,
+        branch: &str,
+        places: BTreeMap<String, PlaceConfig>,
+    ) -> anyhow::Result<BTreeMap<String, PlaceConfig>> {
+        for name in self.only.iter().chain(&self.exclude) {
+            if !places.contains_key(name) {
+                let known: Vec<&str> = places.keys().map(|k| k.as_str()).collect();
+                anyhow::bail!(
+                    "Branch {} has no place named {} (places: {})",
+                    branch,
+                    name,
+                    known.join(", ")
+                );
+            }
+        }
+
+        let selected: BTreeMap<String, PlaceConfig> = places
+            .into_iter()
+            .filter(|(name, _)| self.only.is_empty() || self.only.contains(name))
+            .filter(|(name, _)| !self.exclude.contains(name))
+            .collect();
+        if selected.is_empty() {
+            anyhow::bail!("No places of branch {} are left to deploy", branch);
+        }
+        Ok(selected)
+    }
+}
+
+impl PlannedPlace {
+    fn new(project: &Project, name: &str, place: &PlaceConfig, version_type: VersionType) -> Self {
+        let target = BuildTarget::for_place(name, place);
+
+        PlannedPlace {
+            name: name.to_string(),
+            place_id: place.id,
+            project_file_exists: project.path(&target.project_file).is_file(),
+            project_file: target.project_file,
+            output: target.output,
+            version_type,
+            skip: place.skip,
+        }
+    }
+
+    pub fn target(&self) -> BuildTarget {
+        BuildTarget {
+            name: self.name.clone(),
+            project_file: self.project_file.clone(),
+            output: self.output.clone(),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use super::table::table;
+use crate::config::Project;
+use crate::history::History;
+use clap::Parser;
+
+/// Show what has been deployed, newest first
+#[derive(Debug, Parser)]
+pub struct HistoryCommand {
+    /// Show only deploys to this branch
+    #[clap(short, long, alias = "branch", value_parser)]
+    branch_name: Option<String>,
+    /// Show only this place
+    #[clap(short, long, value_parser)]
+    place: Option<String>,
+    /// Print the records as JSON
+    #[clap(long)]
+    json: bool,
+}
+
+impl HistoryCommand {
+    pub fn run(&self, project: &Project) -> anyhow::Result<Option<String>> {
+        let history = History::new(project);
+        let records: Vec<_> = history
+            .read()?
+            .into_iter()
+            .rev()
+            .filter(|r| self.branch_name.as_ref().is_none_or(|b| &r.branch == b))
+            .filter(|r| self.place.as_ref().is_none_or(|p| &r.place == p))
+            .collect();
+
+        if self.json {
+            return Ok(Some(serde_json::to_string_pretty(&records)?));
+        }
+        if records.is_empty() {
+            return Ok(Some(format!(
+                "No deploys recorded in {}",
+                history.path().display()
+            )));
+        }
+
+        let rows: Vec<Vec<String>> = records
+            .iter()
+            .map(|r| {
+                vec![
+                    r.deploy_id.clone(),
+                    r.timestamp.clone(),
+                    r.branch.clone(),
+                    format!("{} ({})", r.place, r.place_id),
+                    r.version.to_string(),
+                    format!("{:?}", r.version_type),
+                    r.commit
+                        .as_deref()
+                        .map(|c| c.chars().take(7).collect())
+                        .unwrap_or_default(),
+                    r.message.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+
+        Ok(Some(table(
+            &[
+                "DEPLOY",
+                "TIME",
+                "BRANCH",
+                "PLACE",
+                "VERSION",
+                "VERSION TYPE",
+                "COMMIT",
+                "MESSAGE",
+            ],
+            &rows,
+        )))
+    }
+}
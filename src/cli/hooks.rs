@@ -0,0 +1,78 @@
+use super::output::Output;
+use super::task::shell;
+use crate::color::Color;
+use crate::config::{Hook, Hooks, Project};
+use anyhow::Context;
+
+/// What a hook runs for, passed to its commands as `RIT_*` environment variables
+#[derive(Debug, Clone, Default)]
+pub struct HookContext {
+    pub branch: Option<String>,
+    pub place_name: Option<String>,
+    pub place_id: Option<u64>,
+    pub version: Option<u64>,
+    pub artifact: Option<String>,
+}
+
+impl HookContext {
+    fn vars(&self, hook: Hook) -> Vec<(&'static str, String)> {
+        let mut vars = vec![("RIT_HOOK", hook.name().to_string())];
+        vars.extend(self.branch.clone().map(|v| ("RIT_BRANCH", v)));
+        vars.extend(self.place_name.clone().map(|v| ("RIT_PLACE_NAME", v)));
+        vars.extend(self.place_id.map(|v| ("RIT_PLACE_ID", v.to_string())));
+        vars.extend(self.version.map(|v| ("RIT_VERSION", v.to_string())));
+        vars.extend(self.artifact.clone().map(|v| ("RIT_ARTIFACT", v)));
+        vars
+    }
+}
+
+/// Runs the commands of a hook; a failing `pre*` hook stops the operation, a failing post hook is only reported
+pub fn run_hook(
+    project: &Project,
+    hooks: &Hooks,
+    hook: Hook,
+    context: &HookContext,
+    out: &Output,
+) -> anyhow::Result<()> {
+    for command in hooks.commands(hook) {
+        out.status(
+            Color::blue(),
+            "Hook",
+            format!("{} ({})", command, hook.name()),
+        );
+        let mut shell = shell(project, command, context.vars(hook))?;
+
+        // Prefixed output is collected first so lines from places running side by side stay whole
+        let status = if out.is_prefixed() {
+            let output = shell
+                .output()
+                .with_context(|| format!("Unable to run `{command}`"))?;
+            for line in String::from_utf8_lossy(&output.stdout).lines() {
+                out.line(line);
+            }
+            for line in String::from_utf8_lossy(&output.stderr).lines() {
+                out.error(line);
+            }
+            output.status
+        } else {
+            shell
+                .status()
+                .with_context(|| format!("Unable to run `{command}`"))?
+        };
+        if status.success() {
+            continue;
+        }
+
+        if hook.is_pre() {
+            anyhow::bail!("The {} hook `{}` {}", hook.name(), command, status);
+        }
+        out.error(format!(
+            "{} the {} hook `{}` {}",
+            Color::red().pad("Warning"),
+            hook.name(),
+            command,
+            status
+        ));
+    }
+    Ok(())
+}
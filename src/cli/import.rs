@@ -1,5 +1,8 @@
 use super::getenv;
+use super::hooks::{run_hook, HookContext};
+use super::output::Output;
 use crate::color::Color;
+use crate::config::{Config, Hook, Project};
 use crate::rbx::Remodel;
 use clap::Parser;
 
@@ -24,9 +27,27 @@ pub struct ImportCommand {
 }
 
 impl ImportCommand {
-    pub fn run(&self) -> anyhow::Result<Option<String>> {
-        let auth = getenv(self.auth.clone(), "ROBLOSECURITY".to_string());
-        let remodel = Remodel::new(auth);
+    pub fn run(&self, project: &Project) -> anyhow::Result<Option<String>> {
+        let auth = getenv(self.auth.clone(), "ROBLOSECURITY", None)?;
+        let config = Config::load(project)?;
+        let places = config
+            .data
+            .import
+            .as_ref()
+            .map(|import| &import.places)
+            .filter(|places| !places.is_empty());
+
+        if self.file_path.is_none() {
+            if let Some(name) = &self.map_name {
+                if !places.is_some_and(|places| places.contains_key(name)) {
+                    anyhow::bail!("No map named {} in import.places of {}", name, config.path);
+                }
+            } else if self.game_maps && places.is_none() {
+                anyhow::bail!("{} has no maps in import.places", config.path);
+            }
+        }
+
+        let remodel = Remodel::new(auth, &config.data, &project.root);
 
         println!(
             "{} {}",
@@ -44,12 +65,30 @@ impl ImportCommand {
             })
         );
 
+        let context = HookContext {
+            place_name: self.map_name.clone(),
+            place_id: self
+                .map_name
+                .as_ref()
+                .and_then(|name| places?.get(name))
+                .map(|place| place.id),
+            artifact: self.file_path.clone().map(absolute_path).transpose()?,
+            ..HookContext::default()
+        };
+        run_hook(
+            project,
+            &config.data.hooks,
+            Hook::PreImport,
+            &context,
+            &Output::default(),
+        )?;
+
         if self.map_name.is_some() {
             if self.file_path.is_some() {
                 remodel.run(
                     "importLocalMap",
                     &[
-                        self.file_path.clone().unwrap(),
+                        absolute_path(self.file_path.clone().unwrap())?,
                         self.map_name.clone().unwrap(),
                     ],
                 );
@@ -66,6 +105,21 @@ impl ImportCommand {
             }
         }
 
+        run_hook(
+            project,
+            &config.data.hooks,
+            Hook::PostImport,
+            &context,
+            &Output::default(),
+        )?;
         Ok(None)
     }
 }
+
+/// Remodel runs from the project root, so paths given on the command line are made absolute first
+fn absolute_path(path: String) -> anyhow::Result<String> {
+    Ok(std::env::current_dir()?
+        .join(path)
+        .to_string_lossy()
+        .to_string())
+}
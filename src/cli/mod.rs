@@ -1,48 +1,143 @@
 mod build;
+mod config;
 mod datastore;
 mod deploy;
+mod history;
+mod hooks;
 mod import;
 mod init;
 mod open;
+pub mod output;
 mod refresh;
+mod rollback;
 mod run;
 mod send;
 mod sync;
+mod table;
+mod task;
 
+use crate::color::Color;
+use crate::config::{Project, Workspace};
+use anyhow::Context;
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 pub use self::build::BuildCommand;
+pub use self::config::ConfigCommand;
 pub use self::datastore::DataStore;
 pub use self::deploy::DeployCommand;
+pub use self::history::HistoryCommand;
 pub use self::import::ImportCommand;
 pub use self::init::InitCommand;
 pub use self::open::OpenCommand;
 pub use self::refresh::RefreshCommand;
+pub use self::rollback::RollbackCommand;
 pub use self::run::RunCommand;
 pub use self::send::SendCommand;
 pub use self::sync::SyncCommand;
+pub use self::task::TaskCommand;
 
 #[derive(Debug, Parser)]
 #[clap(name = "Rit", version)]
 pub struct Cli {
+    /// The config file to use instead of searching for one
+    #[clap(long, global = true, value_parser)]
+    pub config: Option<PathBuf>,
+
+    /// The workspace member to run the command in; can be given more than once
+    #[clap(short = 'w', long = "workspace", global = true, value_parser)]
+    pub members: Vec<String>,
+
+    /// Run the command in every workspace member
+    #[clap(long, global = true)]
+    pub all_members: bool,
+
     #[clap(subcommand)]
     pub command: Command,
 }
 
 impl Cli {
     pub async fn run(self) -> anyhow::Result<Option<String>> {
+        let projects = self.projects()?;
+
+        // Build, deploy and datastore can run across several workspace members in turn
         match self.command {
             Command::Init(command) => command.run(),
-            Command::Build(command) => command.run(),
-            Command::Open(command) => command.run(),
-            Command::Run(command) => command.run(),
-            Command::Sync(command) => command.run(),
-            Command::Send(command) => command.run().await,
-            Command::Deploy(command) => command.run().await,
-            Command::Import(command) => command.run(),
-            Command::Refresh(command) => command.run(),
-            Command::Datastore(command) => command.run().await,
+            Command::Build(command) => {
+                let mut output = Vec::new();
+                for project in &projects {
+                    announce(project, projects.len());
+                    output.extend(command.run(project)?);
+                }
+                Ok(join(output))
+            }
+            Command::Open(command) => command.run(&single(projects)?),
+            Command::Run(command) => command.run(&single(projects)?),
+            Command::Sync(command) => command.run(&single(projects)?),
+            Command::Send(command) => command.run(&single(projects)?).await,
+            Command::Deploy(command) => {
+                let mut output = Vec::new();
+                for project in &projects {
+                    announce(project, projects.len());
+                    output.extend(command.clone().run(project).await?);
+                }
+                Ok(join(output))
+            }
+            Command::Import(command) => command.run(&single(projects)?),
+            Command::Refresh(command) => command.run(&single(projects)?),
+            Command::Datastore(command) => {
+                let mut output = Vec::new();
+                for project in &projects {
+                    announce(project, projects.len());
+                    output.extend(command.clone().run(project).await?);
+                }
+                Ok(join(output))
+            }
+            Command::Config(command) => command.run(&single(projects)?),
+            Command::Task(command) => command.run(&single(projects)?),
+            Command::History(command) => command.run(&single(projects)?),
+            Command::Rollback(command) => command.run(&single(projects)?).await,
+        }
+    }
+
+    /// The project found from the current directory, or the workspace members picked with -w
+    fn projects(&self) -> anyhow::Result<Vec<Project>> {
+        if self.members.is_empty() && !self.all_members {
+            return Ok(vec![Project::locate(self.config.as_deref())?]);
+        }
+        if self.config.is_some() {
+            anyhow::bail!("--config cannot be combined with --workspace or --all-members");
         }
+
+        let current_dir = std::env::current_dir()?;
+        let workspace = Workspace::locate(&current_dir)?.with_context(|| {
+            format!(
+                "No workspace file found in {} or any parent directory",
+                current_dir.display()
+            )
+        })?;
+        workspace.select(&self.members, self.all_members)
+    }
+}
+
+fn single(mut projects: Vec<Project>) -> anyhow::Result<Project> {
+    if projects.len() != 1 {
+        anyhow::bail!("This command runs in one project at a time; pick a single member with -w");
+    }
+    Ok(projects.remove(0))
+}
+
+fn join(output: Vec<String>) -> Option<String> {
+    if output.is_empty() {
+        None
+    } else {
+        Some(output.join("\n"))
+    }
+}
+
+fn announce(project: &Project, count: usize) {
+    if let (Some(name), true) = (&project.name, count > 1) {
+        println!("{} {}", Color::blue().pad("Member"), name);
     }
 }
 
@@ -58,12 +153,27 @@ pub enum Command {
     Import(ImportCommand),
     Refresh(RefreshCommand),
     Datastore(DataStore),
+    Config(ConfigCommand),
+    Task(TaskCommand),
+    History(HistoryCommand),
+    Rollback(RollbackCommand),
 }
 
-pub fn getenv(api_key: Option<String>, name: String) -> String {
-    match api_key {
-        Some(v) => v,
-        None => std::env::var(name.clone())
-            .unwrap_or_else(|_| panic!("environment variable \"{}\" is not set", name)),
+/// The value given on the command line, or else the value of an environment variable,
+/// naming the branch it was read for when it is not set
+pub fn getenv(value: Option<String>, name: &str, branch: Option<&str>) -> anyhow::Result<String> {
+    if let Some(value) = value {
+        return Ok(value);
+    }
+
+    match (std::env::var(name), branch) {
+        (Ok(value), _) => Ok(value),
+        (Err(_), Some(branch)) => anyhow::bail!(
+            "Environment variable {} is not set for branch {}; set it or add it to .env.{}",
+            name,
+            branch,
+            branch
+        ),
+        (Err(_), None) => anyhow::bail!("Environment variable {} is not set", name),
     }
 }
@@ -1,4 +1,5 @@
 use crate::color::Color;
+use crate::config::Project;
 use clap::Parser;
 use roblox_install::RobloxStudio;
 use std::path::Path;
@@ -17,11 +18,13 @@ fn open_output(file_path: String) -> String {
 }
 
 impl OpenCommand {
-    pub fn run(&self) -> anyhow::Result<Option<String>> {
-        let input = self
-            .file_path
-            .clone()
-            .unwrap_or(format!("build/{}.rbxl", "default"));
+    pub fn run(&self, project: &Project) -> anyhow::Result<Option<String>> {
+        let input = self.file_path.clone().unwrap_or(
+            project
+                .path(format!("build/{}.rbxl", "default"))
+                .to_string_lossy()
+                .to_string(),
+        );
 
         let path = Path::new(&input);
 
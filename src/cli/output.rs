@@ -0,0 +1,38 @@
+use crate::color::Color;
+
+/// Prints status lines, prefixed with the place they belong to when several places run side by side
+#[derive(Debug, Clone, Default)]
+pub struct Output {
+    prefix: Option<String>,
+}
+
+impl Output {
+    /// Output for one of several places, padded so the prefixes of all places line up
+    pub fn for_place(name: &str, width: usize) -> Self {
+        Output {
+            prefix: Some(Color::blue().paint(&format!("{:<width$}", format!("[{name}]")))),
+        }
+    }
+
+    pub fn is_prefixed(&self) -> bool {
+        self.prefix.is_some()
+    }
+
+    pub fn status(&self, color: Color, status: &str, message: impl std::fmt::Display) {
+        self.line(format!("{} {}", color.pad(status), message));
+    }
+
+    pub fn line(&self, line: impl std::fmt::Display) {
+        match &self.prefix {
+            Some(prefix) => println!("{prefix} {line}"),
+            None => println!("{line}"),
+        }
+    }
+
+    pub fn error(&self, line: impl std::fmt::Display) {
+        match &self.prefix {
+            Some(prefix) => eprintln!("{prefix} {line}"),
+            None => eprintln!("{line}"),
+        }
+    }
+}
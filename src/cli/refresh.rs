@@ -1,5 +1,6 @@
 use super::getenv;
 use crate::color::Color;
+use crate::config::{Config, Project};
 use crate::rbx::Remodel;
 use clap::Parser;
 use fs_err as fs;
@@ -30,13 +31,15 @@ fn filter_project_file(filename: &str) -> String {
 }
 
 impl RefreshCommand {
-    pub fn run(&self) -> anyhow::Result<Option<String>> {
-        let auth = getenv(self.auth.clone(), "ROBLOSECURITY".to_string());
-        let remodel = Remodel::new(auth);
+    pub fn run(&self, project: &Project) -> anyhow::Result<Option<String>> {
+        let auth = getenv(self.auth.clone(), "ROBLOSECURITY", None)?;
+        // Map project files follow the import layout of the config, when there is one
+        let data = Config::load_data(project)?;
+        let remodel = Remodel::new(auth, &data, &project.root);
         let project_name = self.project_name.clone().unwrap_or("default".to_string());
 
         if self.all_projects {
-            for entry in fs::read_dir(".")? {
+            for entry in fs::read_dir(&project.root)? {
                 let path = entry.unwrap().path();
                 if let Some(extension) = path.extension() {
                     if extension == "json"
@@ -50,14 +53,14 @@ impl RefreshCommand {
                         let filtered_name = filter_project_file(path.to_string_lossy().as_ref());
 
                         if filtered_name != "default" {
-                            remodel.run("refreshProjectFile", &[filtered_name.clone()]);
+                            remodel.run("refreshProjectFile", std::slice::from_ref(&filtered_name));
                             println!("{} {}", Color::green().pad("Refreshing"), filtered_name);
                         }
                     }
                 }
             }
         } else if self.project_name.is_some() {
-            remodel.run("refreshProjectFile", &[project_name.clone()]);
+            remodel.run("refreshProjectFile", std::slice::from_ref(&project_name));
             println!("{} {}", Color::green().pad("Refreshing"), project_name);
         } else {
             println!("No project name specified!");
@@ -0,0 +1,244 @@
+use super::deploy::prune_artifacts;
+use super::getenv;
+use super::output::Output;
+use crate::color::Color;
+use crate::config::{Config, Project};
+use crate::history::{new_deploy_id, timestamp, Artifacts, DeployRecord, History};
+use crate::rbx::Universe;
+use clap::Parser;
+use std::collections::BTreeMap;
+
+/// Republish the builds of an earlier deploy from .rit/artifacts
+#[derive(Debug, Parser)]
+pub struct RollbackCommand {
+    /// The branch to roll back
+    #[clap(short, long, alias = "branch", value_parser)]
+    branch_name: Option<String>,
+    /// The deploy to go back to, as shown by rit history
+    #[clap(long, value_parser, conflicts_with = "steps")]
+    to: Option<String>,
+    /// How many publishes to go back for each place
+    #[clap(long, value_parser, default_value_t = 1)]
+    steps: usize,
+    /// The Roblox API key
+    #[clap(short, long, value_parser)]
+    api_key: Option<String>,
+}
+
+impl RollbackCommand {
+    pub async fn run(self, project: &Project) -> anyhow::Result<Option<String>> {
+        let config = Config::for_branch(project, self.branch_name.clone())?;
+        let branch = config.branch.clone();
+        let history = History::new(project);
+        let artifacts = Artifacts::new(project);
+        let records = history.read()?;
+
+        let targets = match &self.to {
+            Some(deploy_id) => deploy_targets(&records, &branch, deploy_id)?,
+            None => step_targets(&records, &branch, self.steps)?,
+        };
+
+        // Every build has to be there before anything is republished, so a rollback is never half done
+        for target in &targets {
+            if !artifacts.path(&target.artifact_sha256).is_file() {
+                anyhow::bail!(
+                    "The build of {} from deploy {} is no longer in {}; it was pruned or never stored",
+                    target.place,
+                    target.deploy_id,
+                    artifacts.path(&target.artifact_sha256).display()
+                );
+            }
+        }
+
+        let api_key = getenv(
+            self.api_key.clone(),
+            &config.api_key_env()?,
+            Some(&config.branch),
+        )?;
+        let deploy_id = new_deploy_id();
+        println!(
+            "{} {} {}",
+            Color::green().pad("Rolling back"),
+            branch,
+            match (&self.to, self.steps) {
+                (Some(deploy_id), _) => format!("to deploy {deploy_id}"),
+                (None, 1) => "by 1 step".to_string(),
+                (None, steps) => format!("by {steps} steps"),
+            }
+        );
+
+        for target in targets {
+            let universe = Universe::new(&api_key, target.universe_id, config.data.retry);
+            let path = artifacts.path(&target.artifact_sha256);
+            let version = universe
+                .publish(
+                    &Output::default(),
+                    &target.place,
+                    &path.to_string_lossy(),
+                    target.output,
+                    target.place_id,
+                    target.version_type,
+                )
+                .await?;
+            println!(
+                "{} {} ({}) to deploy {} with version number: {}",
+                Color::green().pad("Restored"),
+                target.place,
+                target.place_id,
+                target.deploy_id,
+                version
+            );
+
+            // The commit is the one that built the restored file, so history shows what is live
+            history.append(&DeployRecord {
+                deploy_id: deploy_id.clone(),
+                timestamp: timestamp(),
+                branch: branch.clone(),
+                version,
+                message: Some(format!("Rollback to {}", target.deploy_id)),
+                rollback_of: Some(target.deploy_id.clone()),
+                ..target.clone()
+            })?;
+        }
+
+        prune_artifacts(&history, &artifacts, &config);
+        Ok(None)
+    }
+}
+
+/// For each place of the branch, its latest publish up to and including one deploy; places the
+/// deploy skipped as unchanged have no record of it, so their build from before it is used
+fn deploy_targets(
+    records: &[DeployRecord],
+    branch: &str,
+    deploy_id: &str,
+) -> anyhow::Result<Vec<DeployRecord>> {
+    let Some(end) = records.iter().rposition(|r| r.deploy_id == deploy_id) else {
+        anyhow::bail!("No deploy {} in the deploy history", deploy_id);
+    };
+    if records[end].branch != branch {
+        anyhow::bail!(
+            "Deploy {} was to branch {}, not {}",
+            deploy_id,
+            records[end].branch,
+            branch
+        );
+    }
+
+    let mut places: BTreeMap<&str, &DeployRecord> = BTreeMap::new();
+    for record in records[..=end].iter().filter(|r| r.branch == branch) {
+        places.insert(&record.place, record);
+    }
+    Ok(places.into_values().cloned().collect())
+}
+
+/// For each place of the branch, the publish `steps` publishes before its latest one
+fn step_targets(
+    records: &[DeployRecord],
+    branch: &str,
+    steps: usize,
+) -> anyhow::Result<Vec<DeployRecord>> {
+    if steps == 0 {
+        anyhow::bail!("--steps must be at least 1");
+    }
+
+    let mut places: BTreeMap<&str, Vec<&DeployRecord>> = BTreeMap::new();
+    for record in records.iter().rev().filter(|r| r.branch == branch) {
+        places.entry(&record.place).or_default().push(record);
+    }
+    if places.is_empty() {
+        anyhow::bail!("Nothing has been deployed to branch {} yet", branch);
+    }
+
+    let mut targets = Vec::new();
+    for (place, publishes) in places {
+        match publishes.get(steps) {
+            Some(target) => targets.push((*target).clone()),
+            None => println!(
+                "{} {}, which has too few publishes to go back --steps {}",
+                Color::blue().pad("Skipping"),
+                place,
+                steps
+            ),
+        }
+    }
+    if targets.is_empty() {
+        anyhow::bail!(
+            "No place of branch {} has enough publishes to go back --steps {}",
+            branch,
+            steps
+        );
+    }
+    Ok(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions(targets: &[DeployRecord]) -> Vec<(&str, u64)> {
+        targets
+            .iter()
+            .map(|t| (t.place.as_str(), t.version))
+            .collect()
+    }
+
+    #[test]
+    fn step_targets_go_back_per_place() {
+        let records = vec![
+            DeployRecord::test("main", "lobby").deployed("a", 1),
+            DeployRecord::test("main", "arena").deployed("a", 1),
+            DeployRecord::test("main", "lobby").deployed("b", 2),
+            DeployRecord::test("qa", "lobby").deployed("c", 9),
+            DeployRecord::test("main", "lobby").deployed("d", 3),
+            DeployRecord::test("main", "arena").deployed("d", 2),
+        ];
+
+        let targets = step_targets(&records, "main", 1).unwrap();
+        assert_eq!(versions(&targets), [("arena", 1), ("lobby", 2)]);
+        let targets = step_targets(&records, "main", 2).unwrap();
+        assert_eq!(versions(&targets), [("lobby", 1)]);
+    }
+
+    #[test]
+    fn step_targets_beyond_the_history_fail() {
+        let records = vec![
+            DeployRecord::test("main", "lobby").deployed("a", 1),
+            DeployRecord::test("main", "lobby").deployed("b", 2),
+        ];
+
+        let err = step_targets(&records, "main", 2).unwrap_err().to_string();
+        assert!(err.contains("enough publishes"), "{err}");
+        assert!(step_targets(&records, "main", 0).is_err());
+        assert!(step_targets(&records, "qa", 1).is_err());
+    }
+
+    #[test]
+    fn deploy_targets_keep_places_skipped_as_unchanged() {
+        let records = vec![
+            DeployRecord::test("main", "lobby").deployed("a", 1),
+            DeployRecord::test("main", "arena").deployed("a", 1),
+            // lobby was unchanged in deploy b
+            DeployRecord::test("main", "arena").deployed("b", 2),
+            DeployRecord::test("main", "lobby").deployed("c", 2),
+            DeployRecord::test("main", "arena").deployed("c", 3),
+        ];
+
+        let targets = deploy_targets(&records, "main", "b").unwrap();
+        assert_eq!(versions(&targets), [("arena", 2), ("lobby", 1)]);
+    }
+
+    #[test]
+    fn deploy_targets_ignore_other_branches() {
+        let records = vec![
+            DeployRecord::test("main", "lobby").deployed("a", 1),
+            DeployRecord::test("qa", "lobby").deployed("b", 1),
+            DeployRecord::test("main", "arena").deployed("c", 1),
+        ];
+
+        let targets = deploy_targets(&records, "main", "c").unwrap();
+        assert_eq!(versions(&targets), [("arena", 1), ("lobby", 1)]);
+        assert!(deploy_targets(&records, "main", "b").is_err());
+        assert!(deploy_targets(&records, "main", "d").is_err());
+    }
+}
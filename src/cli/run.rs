@@ -1,4 +1,8 @@
-use super::{build::build_place, open::OpenCommand};
+use super::build::{build_place, BuildTarget};
+use super::hooks::HookContext;
+use super::open::OpenCommand;
+use super::output::Output;
+use crate::config::{Config, Project};
 use clap::Parser;
 
 /// Build the project and open it in Roblox Studio
@@ -13,13 +17,21 @@ pub struct RunCommand {
 }
 
 impl RunCommand {
-    pub fn run(&self) -> anyhow::Result<Option<String>> {
-        let output = build_place(self.project_name.clone(), self.output_name.clone());
+    pub fn run(&self, project: &Project) -> anyhow::Result<Option<String>> {
+        let data = Config::load_data(project)?;
+        let target = BuildTarget::new(self.project_name.clone(), self.output_name.clone());
+        let output = build_place(
+            project,
+            &target,
+            &data.hooks,
+            &HookContext::default(),
+            &Output::default(),
+        )?;
         let open_command = OpenCommand {
-            file_path: output.clone(),
+            file_path: Some(output),
         };
 
-        open_command.run()?;
+        open_command.run(project)?;
         Ok(None)
     }
 }
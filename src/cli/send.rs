@@ -1,7 +1,7 @@
 use clap::Parser;
 
 use super::getenv;
-use crate::config::Config;
+use crate::config::{Config, Project};
 use crate::rbx::Message;
 
 /// Send a message to MessageService
@@ -22,18 +22,18 @@ pub struct SendCommand {
 }
 
 impl SendCommand {
-    pub async fn run(self) -> anyhow::Result<Option<String>> {
-        let api_key = getenv(self.api_key.clone(), "OPENCLOUD_KEY".to_string());
-        let branch = match self.branch_name.clone() {
-            Some(v) => v,
-            None => "main".to_string(),
-        };
-        let config = Config::new(branch);
-        let universe_id = config.get_universe_id().unwrap();
+    pub async fn run(self, project: &Project) -> anyhow::Result<Option<String>> {
+        let config = Config::for_branch(project, self.branch_name.clone())?;
+        let api_key = getenv(
+            self.api_key.clone(),
+            &config.api_key_env()?,
+            Some(&config.branch),
+        )?;
+        let universe_id = config.get_universe_id()?;
 
-        Message::new(&api_key, universe_id)
+        Message::new(&api_key, universe_id, config.data.retry)
             .publish(&self.topic.clone().unwrap(), &self.message.clone().unwrap())
-            .await;
+            .await?;
 
         Ok(None)
     }
@@ -1,4 +1,5 @@
 use super::getenv;
+use crate::config::Project;
 use anyhow::Ok;
 use clap::Parser;
 use std::process::Command;
@@ -12,19 +13,20 @@ pub struct SyncCommand {
 }
 
 impl SyncCommand {
-    pub fn run(&self) -> anyhow::Result<Option<String>> {
-        img_sync(self.auth.clone())
+    pub fn run(&self, project: &Project) -> anyhow::Result<Option<String>> {
+        img_sync(project, self.auth.clone())
     }
 }
 
-pub fn img_sync(auth: Option<String>) -> anyhow::Result<Option<String>> {
-    let auth = getenv(auth, "ROBLOSECURITY".to_string());
+pub fn img_sync(project: &Project, auth: Option<String>) -> anyhow::Result<Option<String>> {
+    let auth = getenv(auth, "ROBLOSECURITY", None)?;
     Command::new("sh")
         .arg("-c")
         .arg(format!(
             r#"tarmac sync --target roblox --auth "{}" --retry 3 --retry-delay 5"#,
             auth,
         ))
+        .current_dir(&project.root)
         .output()
         .expect("failed to execute process");
 
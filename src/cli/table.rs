@@ -0,0 +1,27 @@
+use crate::color::Color;
+
+/// Lines up rows under a header, padding every column to its widest cell
+pub fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: Vec<String>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let header = line(headers.iter().map(|h| h.to_string()).collect());
+    let mut lines = vec![Color::blue().paint(&header)];
+    lines.extend(rows.iter().map(|row| line(row.clone())));
+    lines.join("\n")
+}
@@ -0,0 +1,111 @@
+use crate::color::Color;
+use crate::config::{task_order, Config, Project, Task};
+use anyhow::Context;
+use clap::Parser;
+use std::ffi::OsStr;
+use std::process::Command;
+use std::time::Instant;
+
+/// Run a task from the config, after the tasks it depends on
+#[derive(Debug, Parser)]
+pub struct TaskCommand {
+    /// The name of the task; lists the tasks when left out
+    #[clap(value_parser)]
+    name: Option<String>,
+}
+
+impl TaskCommand {
+    pub fn run(&self, project: &Project) -> anyhow::Result<Option<String>> {
+        let config = Config::load(project)?;
+        let tasks = &config.data.tasks;
+
+        let Some(name) = &self.name else {
+            if tasks.is_empty() {
+                return Ok(Some(format!("{} has no tasks", config.path)));
+            }
+            let lines: Vec<String> = tasks
+                .iter()
+                .map(|(name, task)| {
+                    if task.depends_on.is_empty() {
+                        name.clone()
+                    } else {
+                        format!("{} (after {})", name, task.depends_on.join(", "))
+                    }
+                })
+                .collect();
+            return Ok(Some(lines.join("\n")));
+        };
+
+        let order = task_order(tasks, name)?;
+        let started = Instant::now();
+
+        for task_name in order {
+            println!("{} {}", Color::blue().pad("Task"), task_name);
+            for step in &tasks[task_name].steps {
+                run_step(project, task_name, &tasks[task_name], step)?;
+            }
+        }
+
+        println!(
+            "{} task {} in {:.2}s",
+            Color::green().pad("Finished"),
+            name,
+            started.elapsed().as_secs_f64()
+        );
+        Ok(None)
+    }
+}
+
+fn run_step(project: &Project, task_name: &str, task: &Task, step: &str) -> anyhow::Result<()> {
+    println!("{} {}", Color::green().pad("Running"), step);
+    let started = Instant::now();
+
+    let status = shell(project, step, &task.env)?
+        .status()
+        .with_context(|| format!("Unable to run `{step}`"))?;
+    let elapsed = started.elapsed().as_secs_f64();
+
+    if !status.success() {
+        println!(
+            "{} {} after {:.2}s",
+            Color::red().pad("Failed"),
+            step,
+            elapsed
+        );
+        anyhow::bail!("Task {} stopped because `{}` {}", task_name, step, status);
+    }
+
+    println!(
+        "{} {} in {:.2}s",
+        Color::green().pad("Finished"),
+        step,
+        elapsed
+    );
+    Ok(())
+}
+
+/// A command run with `sh` from the project root, the way tasks and hooks run their commands
+pub fn shell<K, V>(
+    project: &Project,
+    command: &str,
+    env: impl IntoIterator<Item = (K, V)>,
+) -> anyhow::Result<Command>
+where
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+{
+    // Commands that call rit use this same executable rather than whichever rit is on the PATH
+    let script = match command.strip_prefix("rit") {
+        Some(args) if args.is_empty() || args.starts_with(' ') => format!("\"$RIT\"{args}"),
+        _ => command.to_string(),
+    };
+
+    let mut shell = Command::new("sh");
+    shell
+        .arg("-c")
+        .arg(script)
+        .current_dir(&project.root)
+        .env("RIT", std::env::current_exe()?)
+        .envs(env);
+    Ok(shell)
+}
@@ -0,0 +1,463 @@
+use super::format::ConfigFormat;
+use super::{ConfigData, Issues, Reader};
+use anyhow::Context;
+use fs_err as fs;
+use serde_json::{json, Map, Value};
+use std::ops::Range;
+
+/// A config file opened for editing, keeping its key order and formatting when written back
+pub enum Document {
+    /// JSON has no editing library that keeps formatting, so each edit is spliced into the text
+    Json {
+        json: Value,
+        contents: String,
+        indent: String,
+    },
+    Toml(toml_edit::Document),
+}
+
+impl Document {
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        let format = ConfigFormat::from_path(path)?;
+        let contents = fs::read_to_string(path)?;
+
+        match format {
+            ConfigFormat::Json => Ok(Document::Json {
+                json: format.parse(path, &contents)?,
+                indent: detect_indent(&contents),
+                contents,
+            }),
+            ConfigFormat::Toml => Ok(Document::Toml(
+                contents
+                    .parse()
+                    .with_context(|| format!("{path} is not valid TOML"))?,
+            )),
+            ConfigFormat::Yaml => Err(anyhow::anyhow!(
+                "Editing {path} is not supported because its comments would be lost; edit it by hand"
+            )),
+        }
+    }
+
+    /// The document as raw JSON, the same model every config format is read into
+    pub fn json(&self) -> anyhow::Result<Value> {
+        match self {
+            Document::Json { json, .. } => Ok(json.clone()),
+            Document::Toml(document) => Ok(toml::from_str(&document.to_string())?),
+        }
+    }
+
+    pub fn get(&self, path: &[&str]) -> anyhow::Result<Option<Value>> {
+        let json = self.json()?;
+        let mut current = &json;
+        for part in path {
+            match current.get(part) {
+                Some(v) => current = v,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(current.clone()))
+    }
+
+    /// Sets the value at a key path, creating any missing parent tables
+    pub fn set(&mut self, path: &[&str], value: Value) -> anyhow::Result<()> {
+        let (last, parents) = path.split_last().context("key must not be empty")?;
+
+        match self {
+            Document::Json {
+                json,
+                contents,
+                indent,
+            } => {
+                splice(contents, path, &value, indent)?;
+
+                let mut current = json;
+                for (i, part) in parents.iter().enumerate() {
+                    current = current
+                        .as_object_mut()
+                        .with_context(|| format!("{} is not an object", path[..i].join(".")))?
+                        .entry(part.to_string())
+                        .or_insert_with(|| Value::Object(Map::new()));
+                }
+                current
+                    .as_object_mut()
+                    .with_context(|| format!("{} is not an object", parents.join(".")))?
+                    .insert(last.to_string(), value);
+            }
+            Document::Toml(document) => {
+                let mut current = document.as_table_mut() as &mut dyn toml_edit::TableLike;
+                for (i, part) in parents.iter().enumerate() {
+                    if current.get(part).is_none() {
+                        let mut table = toml_edit::Table::new();
+                        table.set_implicit(true);
+                        current.insert(part, toml_edit::Item::Table(table));
+                    }
+                    current = current
+                        .get_mut(part)
+                        .and_then(|item| item.as_table_like_mut())
+                        .with_context(|| format!("{} is not a table", path[..=i].join(".")))?;
+                }
+                current.insert(last, to_toml(&value)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the edited document against the typed model before it is written, taking
+    /// placeholders to be valid, so variables that are not set do not stop an edit
+    pub fn check(&self, path: &str) -> anyhow::Result<()> {
+        let mut reader = Reader::without_placeholders();
+        ConfigData::read(&mut reader, &self.json()?);
+
+        if !reader.issues.is_empty() {
+            return Err(Issues {
+                path: path.to_string(),
+                issues: reader.issues,
+            })
+            .context("Refusing to write a config file with problems");
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &str) -> anyhow::Result<()> {
+        self.check(path)?;
+
+        let contents = match self {
+            Document::Json { contents, .. } => contents.clone(),
+            Document::Toml(document) => document.to_string(),
+        };
+
+        fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+/// The indentation of the first indented line, so rewritten files keep their style
+fn detect_indent(contents: &str) -> String {
+    contents
+        .lines()
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .find(|indent| !indent.is_empty())
+        .unwrap_or("    ")
+        .to_string()
+}
+
+/// A key of a JSON object in the text of a file
+struct Member {
+    key: String,
+    key_start: usize,
+    value: Range<usize>,
+}
+
+/// Writes a value into JSON text at a key path, replacing the old value or adding the
+/// first missing key to its parent, and leaving every other byte of the text alone
+fn splice(contents: &mut String, path: &[&str], value: &Value, indent: &str) -> anyhow::Result<()> {
+    let mut start = skip_whitespace(contents, 0);
+    let mut inline = false;
+
+    for (depth, key) in path.iter().enumerate() {
+        let (members, end) = object_members(contents, start)
+            .with_context(|| format!("{} is not an object", path[..depth].join(".")))?;
+        // An empty object is written like the object holding it
+        if let Some(first) = members.first() {
+            inline = !contents[start..first.key_start].contains('\n');
+        }
+
+        let member = members.iter().find(|member| member.key == *key);
+        match member {
+            Some(member) if depth + 1 < path.len() => start = member.value.start,
+            Some(member) => {
+                let line_indent = line_indent(contents, member.key_start);
+                let text = render(value, inline, &line_indent, indent)?;
+                contents.replace_range(member.value.clone(), &text);
+                return Ok(());
+            }
+            None => {
+                // The rest of the path does not exist yet, so it is added as one nested value
+                let nested = path[depth + 1..]
+                    .iter()
+                    .rev()
+                    .fold(value.clone(), |v, k| json!({ *k: v }));
+                let key = serde_json::to_string(key)?;
+
+                let (range, text) = match (members.first(), members.last()) {
+                    (Some(first), Some(last)) => {
+                        let (separator, member_indent) = if inline {
+                            (" ".to_string(), line_indent(contents, start))
+                        } else {
+                            let member_indent = line_indent(contents, first.key_start);
+                            (format!("\n{member_indent}"), member_indent)
+                        };
+                        let rendered = render(&nested, inline, &member_indent, indent)?;
+                        let end = last.value.end;
+                        (end..end, format!(",{separator}{key}: {rendered}"))
+                    }
+                    _ if inline => {
+                        let rendered = render(&nested, true, "", indent)?;
+                        (start + 1..end, format!("{key}: {rendered}"))
+                    }
+                    _ => {
+                        let open_indent = line_indent(contents, start);
+                        let member_indent = format!("{open_indent}{indent}");
+                        let rendered = render(&nested, false, &member_indent, indent)?;
+                        let text = format!("\n{member_indent}{key}: {rendered}\n{open_indent}");
+                        (start + 1..end, text)
+                    }
+                };
+                contents.replace_range(range, &text);
+                return Ok(());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a value the way its surroundings are written: on one line inside a one-line
+/// object, and otherwise indented to match the line it starts on
+fn render(value: &Value, inline: bool, line_indent: &str, indent: &str) -> anyhow::Result<String> {
+    if inline {
+        return Ok(serde_json::to_string(value)?);
+    }
+
+    let mut buffer = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+    serde::Serialize::serialize(value, &mut serializer)?;
+
+    let text = String::from_utf8(buffer)?;
+    Ok(text.replace('\n', &format!("\n{line_indent}")))
+}
+
+/// The keys of the object starting at `start`, and the position of its closing brace
+fn object_members(text: &str, start: usize) -> anyhow::Result<(Vec<Member>, usize)> {
+    let bytes = text.as_bytes();
+    if bytes.get(start) != Some(&b'{') {
+        anyhow::bail!("expected an object at byte {start}");
+    }
+
+    let mut members = Vec::new();
+    let mut i = skip_whitespace(text, start + 1);
+    loop {
+        match bytes.get(i) {
+            Some(b'}') => return Ok((members, i)),
+            Some(b'"') => {}
+            _ => anyhow::bail!("expected a key at byte {i}"),
+        }
+
+        let key_end = value_end(text, i)?;
+        let key = serde_json::from_str(&text[i..key_end])?;
+        let colon = skip_whitespace(text, key_end);
+        if bytes.get(colon) != Some(&b':') {
+            anyhow::bail!("expected `:` at byte {colon}");
+        }
+        let value_start = skip_whitespace(text, colon + 1);
+        let end = value_end(text, value_start)?;
+        members.push(Member {
+            key,
+            key_start: i,
+            value: value_start..end,
+        });
+
+        i = skip_whitespace(text, end);
+        if bytes.get(i) == Some(&b',') {
+            i = skip_whitespace(text, i + 1);
+        }
+    }
+}
+
+/// The position just past the JSON value starting at `start`
+fn value_end(text: &str, start: usize) -> anyhow::Result<usize> {
+    let bytes = text.as_bytes();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, &byte) in bytes.iter().enumerate().skip(start) {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => {
+                    in_string = false;
+                    if depth == 0 {
+                        return Ok(i + 1);
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(i + 1);
+                }
+            }
+            b',' | b'}' | b']' | b' ' | b'\t' | b'\r' | b'\n' if depth == 0 => return Ok(i),
+            _ => {}
+        }
+    }
+
+    if depth == 0 && !in_string && start < bytes.len() {
+        return Ok(bytes.len());
+    }
+    anyhow::bail!("unexpected end of JSON")
+}
+
+fn skip_whitespace(text: &str, start: usize) -> usize {
+    let rest = &text[start..];
+    start + rest.len() - rest.trim_start().len()
+}
+
+/// The indentation of the line holding a position
+fn line_indent(text: &str, position: usize) -> String {
+    let line_start = text[..position].rfind('\n').map_or(0, |i| i + 1);
+    let line = &text[line_start..];
+    line[..line.len() - line.trim_start().len()].to_string()
+}
+
+fn to_toml(value: &Value) -> anyhow::Result<toml_edit::Item> {
+    match value {
+        Value::Object(object) => {
+            let mut table = toml_edit::Table::new();
+            for (key, v) in object {
+                table.insert(key, to_toml(v)?);
+            }
+            Ok(toml_edit::Item::Table(table))
+        }
+        other => Ok(toml_edit::Item::Value(to_toml_value(other)?)),
+    }
+}
+
+fn to_toml_value(value: &Value) -> anyhow::Result<toml_edit::Value> {
+    Ok(match value {
+        Value::Null => anyhow::bail!("TOML has no null value"),
+        Value::Bool(b) => (*b).into(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into(),
+            None => n
+                .as_f64()
+                .context("number is out of range for TOML")?
+                .into(),
+        },
+        Value::String(s) => s.as_str().into(),
+        Value::Array(items) => {
+            let mut array = toml_edit::Array::new();
+            for item in items {
+                array.push(to_toml_value(item)?);
+            }
+            array.into()
+        }
+        Value::Object(object) => {
+            let mut table = toml_edit::InlineTable::new();
+            for (key, v) in object {
+                table.insert(key, to_toml_value(v)?);
+            }
+            table.into()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edited(contents: &str, path: &[&str], value: Value) -> String {
+        let indent = detect_indent(contents);
+        let mut contents = contents.to_string();
+        splice(&mut contents, path, &value, &indent).unwrap();
+        assert!(
+            serde_json::from_str::<Value>(&contents).is_ok(),
+            "{contents}"
+        );
+        contents
+    }
+
+    #[test]
+    fn keeps_compact_objects_on_one_line() {
+        let contents = "{\"universes\": {\"main\": 1}, \"tags\": [\"a\", \"b\"]}\n";
+
+        assert_eq!(
+            edited(contents, &["universes", "qa"], json!(2)),
+            "{\"universes\": {\"main\": 1, \"qa\": 2}, \"tags\": [\"a\", \"b\"]}\n"
+        );
+        assert_eq!(
+            edited(contents, &["universes", "main"], json!(3)),
+            "{\"universes\": {\"main\": 3}, \"tags\": [\"a\", \"b\"]}\n"
+        );
+    }
+
+    #[test]
+    fn follows_tab_indentation() {
+        let contents = "{\n\t\"universes\": {\n\t\t\"main\": 1\n\t}\n}";
+
+        assert_eq!(
+            edited(contents, &["universes", "qa"], json!(2)),
+            "{\n\t\"universes\": {\n\t\t\"main\": 1,\n\t\t\"qa\": 2\n\t}\n}"
+        );
+    }
+
+    #[test]
+    fn inserts_into_empty_objects() {
+        assert_eq!(edited("{}", &["retry"], json!(1)), "{\n    \"retry\": 1\n}");
+        assert_eq!(
+            edited("{\n  \"places\": {}\n}\n", &["places", "lobby"], json!(5)),
+            "{\n  \"places\": {\n    \"lobby\": 5\n  }\n}\n"
+        );
+        assert_eq!(
+            edited("{\"a\": 1, \"places\": {}}", &["places", "lobby"], json!(5)),
+            "{\"a\": 1, \"places\": {\"lobby\": 5}}"
+        );
+    }
+
+    #[test]
+    fn creates_missing_parents() {
+        let contents = "{\n    \"deployment\": {\n        \"universes\": {}\n    }\n}\n";
+
+        assert_eq!(
+            edited(contents, &["deployment", "places", "qa", "lobby"], json!(5)),
+            "{\n    \"deployment\": {\n        \"universes\": {},\n        \"places\": {\n            \"qa\": {\n                \"lobby\": 5\n            }\n        }\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn replaces_whole_objects() {
+        let contents = "{\n    \"retry\": {\n        \"attempts\": 2\n    },\n    \"artifacts\": {\"keep\": 3}\n}\n";
+
+        assert_eq!(
+            edited(contents, &["retry"], json!({ "attempts": 5, "maxDelayMs": 10 })),
+            "{\n    \"retry\": {\n        \"attempts\": 5,\n        \"maxDelayMs\": 10\n    },\n    \"artifacts\": {\"keep\": 3}\n}\n"
+        );
+    }
+
+    #[test]
+    fn skips_over_strings_with_json_characters() {
+        let contents = "{\"hooks\": {\"preBuild\": \"echo \\\"}, {\\\" ]\"}, \"tasks\": {}}";
+
+        let result = edited(contents, &["hooks", "postBuild"], json!("done"));
+        assert_eq!(
+            result,
+            "{\"hooks\": {\"preBuild\": \"echo \\\"}, {\\\" ]\", \"postBuild\": \"done\"}, \"tasks\": {}}"
+        );
+        assert_eq!(
+            serde_json::from_str::<Value>(&result).unwrap()["hooks"]["preBuild"],
+            "echo \"}, {\" ]"
+        );
+    }
+
+    #[test]
+    fn rejects_paths_through_other_values() {
+        let mut contents = "{\"retry\": 5, \"tags\": [\"a\"]}".to_string();
+
+        let err = splice(&mut contents, &["retry", "attempts"], &json!(1), "    ").unwrap_err();
+        assert_eq!(err.to_string(), "retry is not an object");
+        let err = splice(&mut contents, &["tags", "a"], &json!(1), "    ").unwrap_err();
+        assert_eq!(err.to_string(), "tags is not an object");
+        assert_eq!(contents, "{\"retry\": 5, \"tags\": [\"a\"]}");
+    }
+}
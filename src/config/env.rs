@@ -0,0 +1,51 @@
+use anyhow::Context;
+use std::collections::HashSet;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// The variables that were set before any env file was loaded
+static SHELL_VARS: OnceLock<HashSet<OsString>> = OnceLock::new();
+
+/// The variables set by the last branch env file, with the values they replaced
+static BRANCH_VARS: Mutex<Vec<(String, Option<OsString>)>> = Mutex::new(Vec::new());
+
+/// Remembers which variables come from the shell, so env files never override them;
+/// call this before `.env` is loaded
+pub fn snapshot_shell() {
+    SHELL_VARS.get_or_init(|| std::env::vars_os().map(|(key, _)| key).collect());
+}
+
+/// Loads `.env.<branch>` from the project root over `.env`, undoing any branch file loaded before it
+pub fn load_branch_env(root: &Path, branch: &str) -> anyhow::Result<Option<PathBuf>> {
+    let mut loaded = BRANCH_VARS.lock().unwrap();
+    for (key, previous) in loaded.drain(..).rev() {
+        match previous {
+            Some(value) => std::env::set_var(key, value),
+            None => std::env::remove_var(key),
+        }
+    }
+
+    let path = root.join(format!(".env.{branch}"));
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    // `from_path` never replaces variables that are already set, but the branch file should
+    // override `.env`, so its entries are set one by one, skipping those from the shell
+    let items = dotenvy::from_path_iter(&path)?;
+
+    let shell = SHELL_VARS.get();
+    for item in items {
+        let (key, value) =
+            item.with_context(|| format!("{} is not a valid env file", path.display()))?;
+        if shell.is_some_and(|vars| vars.contains(OsStr::new(&key))) {
+            continue;
+        }
+
+        loaded.push((key.clone(), std::env::var_os(&key)));
+        std::env::set_var(key, value);
+    }
+
+    Ok(Some(path))
+}
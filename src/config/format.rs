@@ -0,0 +1,87 @@
+use anyhow::Context;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// The file names a config can be loaded from, in order of preference
+pub const CONFIG_FILES: [&str; 4] = ["config.json", "rit.toml", "rit.yaml", "rit.yml"];
+
+/// The file names of a personal config that is merged over the shared one
+pub const LOCAL_CONFIG_FILES: [&str; 4] = [
+    "config.local.json",
+    "rit.local.toml",
+    "rit.local.yaml",
+    "rit.local.yml",
+];
+
+/// The syntax of a config file, detected from its file name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    pub fn from_path(path: &str) -> anyhow::Result<Self> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|v| v.to_str())
+            .unwrap_or_default();
+
+        match extension {
+            "json" => Ok(ConfigFormat::Json),
+            "toml" => Ok(ConfigFormat::Toml),
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            _ => Err(anyhow::anyhow!(
+                "Unable to tell the format of {path}; expected a .json, .toml, .yaml or .yml file"
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "JSON",
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Yaml => "YAML",
+        }
+    }
+
+    /// Parses the contents of a config file into the same JSON model regardless of format
+    pub fn parse(&self, path: &str, contents: &str) -> anyhow::Result<Value> {
+        let result = match self {
+            ConfigFormat::Json => serde_json::from_str(contents).map_err(anyhow::Error::from),
+            ConfigFormat::Toml => toml::from_str(contents).map_err(anyhow::Error::from),
+            ConfigFormat::Yaml => serde_yaml::from_str(contents).map_err(anyhow::Error::from),
+        };
+
+        result.with_context(|| format!("{} is not valid {}", path, self.name()))
+    }
+}
+
+/// Finds the config file in a directory, if it has one
+pub fn find_config(dir: &Path) -> anyhow::Result<Option<PathBuf>> {
+    find_one(dir, &CONFIG_FILES)
+}
+
+/// Finds the local config file in a directory, if it has one
+pub fn find_local_config(dir: &Path) -> anyhow::Result<Option<PathBuf>> {
+    find_one(dir, &LOCAL_CONFIG_FILES)
+}
+
+fn find_one(dir: &Path, names: &[&str]) -> anyhow::Result<Option<PathBuf>> {
+    let found: Vec<&str> = names
+        .iter()
+        .copied()
+        .filter(|name| dir.join(name).is_file())
+        .collect();
+
+    match found.as_slice() {
+        [] => Ok(None),
+        [name] => Ok(Some(dir.join(name))),
+        names => Err(anyhow::anyhow!(
+            "Found more than one config file in {} ({}); keep only one",
+            dir.display(),
+            names.join(", ")
+        )),
+    }
+}
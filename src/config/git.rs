@@ -0,0 +1,94 @@
+use super::parse::Reader;
+use anyhow::Context;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::path::Path;
+use std::process::Command;
+
+/// Maps git branches (or glob patterns of them) to config branches, in the order they are written
+#[derive(Debug, Clone, Default)]
+pub struct GitBranches(pub Vec<(String, String)>);
+
+impl GitBranches {
+    pub fn read(reader: &mut Reader, json: &Value, pointer: &str) -> Option<Self> {
+        let object = reader.object(json, pointer)?;
+        let rules = object
+            .iter()
+            .filter_map(|(pattern, v)| {
+                let branch = reader.value(v, &Reader::pointer(pointer, pattern))?;
+                Some((pattern.clone(), branch))
+            })
+            .collect();
+
+        Some(GitBranches(rules))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The config branch for a git branch; exact names win over patterns
+    pub fn resolve(&self, git_branch: &str) -> Option<&str> {
+        let exact = self.0.iter().find(|(pattern, _)| pattern == git_branch);
+        let matched = exact.or_else(|| {
+            self.0.iter().find(|(pattern, _)| {
+                glob::Pattern::new(pattern).is_ok_and(|p| p.matches(git_branch))
+            })
+        });
+
+        matched.map(|(_, branch)| branch.as_str())
+    }
+}
+
+impl Serialize for GitBranches {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(k, v)| (k, v)))
+    }
+}
+
+impl<'de> Deserialize<'de> for GitBranches {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Map::<String, Value>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(k, v)| Ok((k, String::deserialize(v).map_err(D::Error::custom)?)))
+            .collect::<Result<_, _>>()
+            .map(GitBranches)
+    }
+}
+
+/// The name of the checked out git branch
+pub fn current_branch(root: &Path) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .args(["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .current_dir(root)
+        .output()
+        .context("Unable to run git to find the current branch")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "Unable to find the current git branch ({}); pass -b to choose a branch",
+            match stderr.trim() {
+                "" => "HEAD is detached",
+                reason => reason,
+            }
+        );
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+/// The commit checked out in the project, if it is in a git repository
+pub fn current_commit(root: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(root)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_string())
+}
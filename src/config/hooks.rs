@@ -0,0 +1,117 @@
+use super::parse::Reader;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A point in a build, deploy or import where hooks run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    PreBuild,
+    PostBuild,
+    PreDeploy,
+    PostDeploy,
+    PostPublishPlace,
+    PreImport,
+    PostImport,
+}
+
+impl Hook {
+    pub const ALL: [Hook; 7] = [
+        Hook::PreBuild,
+        Hook::PostBuild,
+        Hook::PreDeploy,
+        Hook::PostDeploy,
+        Hook::PostPublishPlace,
+        Hook::PreImport,
+        Hook::PostImport,
+    ];
+
+    /// The config key of the hook
+    pub fn name(&self) -> &'static str {
+        match self {
+            Hook::PreBuild => "preBuild",
+            Hook::PostBuild => "postBuild",
+            Hook::PreDeploy => "preDeploy",
+            Hook::PostDeploy => "postDeploy",
+            Hook::PostPublishPlace => "postPublishPlace",
+            Hook::PreImport => "preImport",
+            Hook::PostImport => "postImport",
+        }
+    }
+
+    /// Whether a failing command stops the operation the hook runs before
+    pub fn is_pre(&self) -> bool {
+        matches!(self, Hook::PreBuild | Hook::PreDeploy | Hook::PreImport)
+    }
+}
+
+/// Shell commands run at fixed points of build, deploy and import
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hooks {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_build: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_build: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_deploy: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_deploy: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_publish_place: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_import: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_import: Vec<String>,
+}
+
+impl Hooks {
+    pub fn read(reader: &mut Reader, json: &Value, pointer: &str) -> Option<Self> {
+        let object = reader.object(json, pointer)?;
+        let mut hooks = Hooks::default();
+
+        for (key, v) in object {
+            let key_pointer = Reader::pointer(pointer, key);
+            let Some(hook) = Hook::ALL.into_iter().find(|hook| hook.name() == key) else {
+                let known: Vec<&str> = Hook::ALL.iter().map(|hook| hook.name()).collect();
+                reader.issue(
+                    &key_pointer,
+                    format!("unknown hook, expected one of {}", known.join(", ")),
+                );
+                continue;
+            };
+
+            // A single command can be written without the list around it
+            let commands = match v {
+                Value::Array(_) => reader.value(v, &key_pointer),
+                _ => reader.value(v, &key_pointer).map(|command| vec![command]),
+            };
+            *hooks.commands_mut(hook) = commands.unwrap_or_default();
+        }
+
+        Some(hooks)
+    }
+
+    pub fn commands(&self, hook: Hook) -> &[String] {
+        match hook {
+            Hook::PreBuild => &self.pre_build,
+            Hook::PostBuild => &self.post_build,
+            Hook::PreDeploy => &self.pre_deploy,
+            Hook::PostDeploy => &self.post_deploy,
+            Hook::PostPublishPlace => &self.post_publish_place,
+            Hook::PreImport => &self.pre_import,
+            Hook::PostImport => &self.post_import,
+        }
+    }
+
+    fn commands_mut(&mut self, hook: Hook) -> &mut Vec<String> {
+        match hook {
+            Hook::PreBuild => &mut self.pre_build,
+            Hook::PostBuild => &mut self.post_build,
+            Hook::PreDeploy => &mut self.pre_deploy,
+            Hook::PostDeploy => &mut self.post_deploy,
+            Hook::PostPublishPlace => &mut self.post_publish_place,
+            Hook::PreImport => &mut self.pre_import,
+            Hook::PostImport => &mut self.post_import,
+        }
+    }
+}
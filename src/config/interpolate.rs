@@ -0,0 +1,128 @@
+use std::fmt;
+
+/// Why a string could not be expanded
+#[derive(Debug, PartialEq, Eq)]
+pub enum InterpolateError {
+    /// A placeholder that is not closed or has no name
+    Invalid(String),
+    /// A variable that is not set, used by a placeholder without a default
+    Unset(String),
+}
+
+impl fmt::Display for InterpolateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterpolateError::Invalid(message) => write!(f, "{message}"),
+            InterpolateError::Unset(name) => write!(
+                f,
+                "environment variable {name} is not set and has no default"
+            ),
+        }
+    }
+}
+
+/// Expands `${NAME}` and `${NAME:-default}` placeholders using the environment
+pub fn interpolate(text: &str) -> Result<String, InterpolateError> {
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            return Err(InterpolateError::Invalid(format!(
+                "unterminated placeholder in {text:?}"
+            )));
+        };
+        let placeholder = &after[..end];
+
+        let (name, default) = match placeholder.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (placeholder, None),
+        };
+        if name.is_empty() {
+            return Err(InterpolateError::Invalid(format!(
+                "empty placeholder in {text:?}"
+            )));
+        }
+
+        match (std::env::var(name).ok().filter(|v| !v.is_empty()), default) {
+            (Some(value), _) => result.push_str(&value),
+            (None, Some(default)) => result.push_str(default),
+            (None, None) => return Err(InterpolateError::Unset(name.to_string())),
+        }
+
+        rest = &after[end + 1..];
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_set_variables() {
+        std::env::set_var("RIT_TEST_INTERPOLATE_SET", "42");
+        assert_eq!(
+            interpolate("id-${RIT_TEST_INTERPOLATE_SET}-${RIT_TEST_INTERPOLATE_SET:-0}"),
+            Ok("id-42-42".to_string())
+        );
+    }
+
+    #[test]
+    fn falls_back_to_defaults() {
+        assert_eq!(
+            interpolate("${RIT_TEST_INTERPOLATE_MISSING:-7}"),
+            Ok("7".to_string())
+        );
+        assert_eq!(
+            interpolate("${RIT_TEST_INTERPOLATE_MISSING:-}"),
+            Ok(String::new())
+        );
+
+        // An empty variable counts as not set
+        std::env::set_var("RIT_TEST_INTERPOLATE_EMPTY", "");
+        assert_eq!(
+            interpolate("${RIT_TEST_INTERPOLATE_EMPTY:-8}"),
+            Ok("8".to_string())
+        );
+    }
+
+    #[test]
+    fn reports_unset_variables() {
+        assert_eq!(
+            interpolate("${RIT_TEST_INTERPOLATE_MISSING}"),
+            Err(InterpolateError::Unset(
+                "RIT_TEST_INTERPOLATE_MISSING".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_placeholders() {
+        assert!(matches!(
+            interpolate("${RIT_TEST_INTERPOLATE_SET"),
+            Err(InterpolateError::Invalid(_))
+        ));
+        assert!(matches!(
+            interpolate("${}"),
+            Err(InterpolateError::Invalid(_))
+        ));
+        assert!(matches!(
+            interpolate("${:-default}"),
+            Err(InterpolateError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn leaves_plain_text_alone() {
+        assert_eq!(
+            interpolate("no $placeholders {here}"),
+            Ok("no $placeholders {here}".to_string())
+        );
+    }
+}
@@ -0,0 +1,312 @@
+mod edit;
+mod env;
+mod format;
+mod git;
+mod hooks;
+mod interpolate;
+mod model;
+mod parse;
+mod project;
+mod schema;
+mod task;
+mod validate;
+mod workspace;
+
+pub use edit::Document;
+pub use env::snapshot_shell;
+pub use format::ConfigFormat;
+pub use git::current_commit;
+pub use hooks::{Hook, Hooks};
+pub use model::*;
+pub use parse::{Issue, Issues, Reader};
+pub use project::Project;
+pub use schema::schema;
+pub use task::{task_order, Task};
+pub use validate::validate;
+pub use workspace::Workspace;
+
+use crate::color::Color;
+use anyhow::Context;
+use fs_err as fs;
+use git::GitBranches;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const DEFAULT_BRANCH: &str = "main";
+
+/// The environment variable holding the Open Cloud key when a branch does not name one
+pub const API_KEY_ENV: &str = "OPENCLOUD_KEY";
+
+#[derive(Debug)]
+pub struct Config {
+    pub path: String,
+    pub data: ConfigData,
+    pub branch: String,
+}
+
+/// Reads a JSON, TOML or YAML config file into raw JSON
+pub fn read_json(path: &str) -> anyhow::Result<Value> {
+    let format = ConfigFormat::from_path(path)?;
+    let contents = fs::read_to_string(path)?;
+    format.parse(path, &contents)
+}
+
+/// A config file with the local config file next to it merged over it
+pub struct Merged {
+    pub json: Value,
+    pub local: Option<(String, Value)>,
+}
+
+impl Merged {
+    /// The file a value came from, given its JSON pointer
+    pub fn source<'a>(&'a self, path: &'a str, pointer: &str) -> &'a str {
+        match &self.local {
+            Some((local_path, local)) if local.pointer(pointer).is_some() => local_path,
+            _ => path,
+        }
+    }
+
+    /// Names the config file along with the local file merged over it, for error messages
+    pub fn label(&self, path: &str) -> String {
+        match &self.local {
+            Some((local_path, _)) => format!("{path} (with {local_path})"),
+            None => path.to_string(),
+        }
+    }
+}
+
+/// Reads a config file and merges the local config file over it
+pub fn read_merged(path: &str) -> anyhow::Result<Merged> {
+    let mut json = read_json(path)?;
+
+    let dir = Path::new(path).parent().unwrap_or(Path::new("."));
+    let local = match format::find_local_config(dir)? {
+        Some(local_path) => {
+            let local_path = local_path.display().to_string();
+            let local = read_json(&local_path)?;
+            merge(&mut json, local.clone());
+            Some((local_path, local))
+        }
+        None => None,
+    };
+
+    Ok(Merged { json, local })
+}
+
+/// Merges objects key by key; any other value replaces the one underneath it
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Reads a config file into its typed model, along with any problems found on the way
+/// and a label naming the files it was read from
+pub fn read_data(path: &str) -> anyhow::Result<(ConfigData, Vec<Issue>, String)> {
+    let merged = read_merged(path)?;
+
+    let mut reader = Reader::new();
+    let data = ConfigData::read(&mut reader, &merged.json);
+
+    Ok((data, reader.issues, merged.label(path)))
+}
+
+impl Config {
+    /// Loads the config for a branch, reporting unset variables only in the values of
+    /// that branch and the branches it extends
+    pub fn new(project: &Project, branch: String) -> anyhow::Result<Self> {
+        Config::read(project, branch, true)
+    }
+
+    fn read(project: &Project, branch: String, check_branch: bool) -> anyhow::Result<Self> {
+        let path = project.config_path()?.display().to_string();
+        let (data, mut issues, label) = read_data(&path)?;
+
+        // Values of other branches may use variables from env files that are not loaded
+        let branches = match (&data.deployment, check_branch) {
+            (_, false) => Vec::new(),
+            (Some(deployment), true) => deployment
+                .chain(&branch)
+                .unwrap_or_else(|_| vec![branch.as_str()]),
+            (None, true) => vec![branch.as_str()],
+        };
+        issues.retain(|issue| issue.applies_to(&branches));
+
+        if !issues.is_empty() {
+            return Err(Issues {
+                path: label,
+                issues,
+            }
+            .into());
+        }
+
+        Ok(Config { path, data, branch })
+    }
+
+    /// Loads the config for the branch given with `-b`, or else the branch mapped from git,
+    /// after loading the `.env.<branch>` file of that branch
+    pub fn for_branch(project: &Project, branch_name: Option<String>) -> anyhow::Result<Self> {
+        let branch = match branch_name {
+            Some(branch) => branch,
+            None => Config::git_branch(project)?,
+        };
+
+        // Placeholders may refer to variables from the branch env file, so it is loaded before the config is read
+        if let Some(path) = env::load_branch_env(&project.root, &branch)? {
+            eprintln!("{} {}", Color::blue().pad("Loaded"), path.display());
+        }
+
+        Config::new(project, branch)
+    }
+
+    /// The branch mapped from the checked out git branch, or the default branch without `gitBranches`
+    ///
+    /// Only `gitBranches` is read, as the rest of the config may need the env file of the branch.
+    fn git_branch(project: &Project) -> anyhow::Result<String> {
+        let path = project.config_path()?.display().to_string();
+        let merged = read_merged(&path)?;
+
+        let mut reader = Reader::new();
+        let git_branches = merged
+            .json
+            .get("gitBranches")
+            .and_then(|v| GitBranches::read(&mut reader, v, "/gitBranches"))
+            .unwrap_or_default();
+        if !reader.issues.is_empty() {
+            return Err(Issues {
+                path: merged.label(&path),
+                issues: reader.issues,
+            }
+            .into());
+        }
+
+        if git_branches.is_empty() {
+            eprintln!(
+                "{} {} (default; no gitBranches)",
+                Color::blue().pad("Branch"),
+                DEFAULT_BRANCH
+            );
+            return Ok(DEFAULT_BRANCH.to_string());
+        }
+
+        let git_branch = git::current_branch(&project.root)?;
+        let Some(branch) = git_branches.resolve(&git_branch) else {
+            anyhow::bail!(
+                "Git branch {} is not mapped in gitBranches of {}; pass -b to choose a branch",
+                git_branch,
+                path
+            );
+        };
+
+        eprintln!(
+            "{} {} (from git branch {})",
+            Color::blue().pad("Branch"),
+            branch,
+            git_branch
+        );
+        Ok(branch.to_string())
+    }
+
+    /// Loads the config for commands that do not target a branch, so the variables of
+    /// branch values are not needed
+    pub fn load(project: &Project) -> anyhow::Result<Self> {
+        Config::read(project, DEFAULT_BRANCH.to_string(), false)
+    }
+
+    /// The config data of a project, or the defaults for commands that also work without a config file
+    pub fn load_data(project: &Project) -> anyhow::Result<ConfigData> {
+        match project.config_path {
+            Some(_) => Ok(Config::load(project)?.data),
+            None => Ok(ConfigData::default()),
+        }
+    }
+
+    fn deployment(&self) -> anyhow::Result<&Deployment> {
+        self.data
+            .deployment
+            .as_ref()
+            .with_context(|| format!("{} has no \"deployment\" section", self.path))
+    }
+
+    /// The environment variable holding the Open Cloud key for the current branch
+    pub fn api_key_env(&self) -> anyhow::Result<String> {
+        let name = match &self.data.deployment {
+            Some(deployment) => deployment.api_key_env(&self.branch)?,
+            None => None,
+        };
+        Ok(name.unwrap_or(API_KEY_ENV).to_string())
+    }
+
+    /// The version type for places of the current branch that do not set their own
+    pub fn version_type(&self) -> anyhow::Result<VersionType> {
+        Ok(self
+            .deployment()?
+            .version_type(&self.branch)?
+            .unwrap_or_default())
+    }
+
+    pub fn get_universe_id(&self) -> anyhow::Result<u64> {
+        self.deployment()?.universe_id(&self.branch)
+    }
+
+    pub fn get_places(&self) -> anyhow::Result<BTreeMap<String, PlaceConfig>> {
+        self.deployment()?.resolve_places(&self.branch)
+    }
+
+    /// The name and scope of a DataStore alias on the current branch
+    pub fn get_store(&self, alias: &str) -> anyhow::Result<(String, String)> {
+        let Some(store) = self.data.datastores.get(alias) else {
+            let known: Vec<&str> = self.data.datastores.keys().map(|k| k.as_str()).collect();
+            anyhow::bail!(
+                "No datastore named {} in {} (known stores: {})",
+                alias,
+                self.path,
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            );
+        };
+
+        // Overrides follow the branch chain, so a branch inherits the stores of the branch it extends
+        let chain = match &self.data.deployment {
+            Some(deployment) => deployment.chain(&self.branch)?,
+            None => vec![self.branch.as_str()],
+        };
+        let overrides: Vec<&DataStoreOverride> = chain
+            .iter()
+            .filter_map(|b| store.branches.get(*b))
+            .collect();
+
+        let name = overrides
+            .iter()
+            .find_map(|o| o.name.clone())
+            .unwrap_or_else(|| store.name.clone());
+        let scope = overrides
+            .iter()
+            .find_map(|o| o.scope.clone())
+            .or_else(|| store.scope.clone())
+            .unwrap_or_else(|| "global".to_string());
+
+        Ok((name, scope))
+    }
+
+    pub fn get_datastore(&self) -> (Option<String>, Option<String>) {
+        match &self.data.datastore {
+            Some(ds) => (Some(ds.name.clone()), Some(ds.scope.clone())),
+            None => (None, None),
+        }
+    }
+}
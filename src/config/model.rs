@@ -0,0 +1,667 @@
+use super::git::GitBranches;
+use super::hooks::Hooks;
+use super::parse::Reader;
+use super::task::Task;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The typed contents of a config file
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ConfigData {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deployment: Option<Deployment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub import: Option<Import>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datastore: Option<DataStoreConfig>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub datastores: BTreeMap<String, DataStoreAlias>,
+    #[serde(
+        default,
+        rename = "gitBranches",
+        skip_serializing_if = "GitBranches::is_empty"
+    )]
+    pub git_branches: GitBranches,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tasks: BTreeMap<String, Task>,
+    #[serde(default)]
+    pub hooks: Hooks,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    #[serde(default)]
+    pub artifacts: ArtifactRetention,
+}
+
+/// Universes and places to deploy to, keyed by branch
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Deployment {
+    #[serde(default)]
+    pub universes: BTreeMap<String, u64>,
+    #[serde(default)]
+    pub places: BTreeMap<String, BranchPlaces>,
+    /// The environment variable holding the Open Cloud key of each branch
+    #[serde(
+        default,
+        rename = "apiKeyEnv",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub api_key_env: BTreeMap<String, String>,
+    /// The version type of places on each branch that do not set their own
+    #[serde(
+        default,
+        rename = "versionType",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub version_type: BTreeMap<String, VersionType>,
+}
+
+/// The places of a single branch, optionally inheriting the places of another branch
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct BranchPlaces {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    #[serde(flatten)]
+    pub places: BTreeMap<String, PlaceConfig>,
+}
+
+/// How a single place is built and published; a bare place id uses the defaults
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaceConfig {
+    pub id: u64,
+    /// The project file to build, defaulting to `<name>.project.json`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_type: Option<VersionType>,
+    #[serde(default)]
+    pub skip: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<OutputFormat>,
+}
+
+/// Whether a publish only saves the place or also makes it live
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ValueEnum)]
+pub enum VersionType {
+    #[serde(alias = "saved")]
+    Saved,
+    #[default]
+    #[serde(alias = "published")]
+    Published,
+}
+
+/// The file format a place is built to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Rbxl,
+    Rbxlx,
+}
+
+/// Places that maps and assets are imported from
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Import {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assets_place_id: Option<u64>,
+    #[serde(default)]
+    pub places: BTreeMap<String, ImportPlace>,
+}
+
+/// A place that a map is imported from; a bare place id uses the default layout
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ImportPlace {
+    pub id: u64,
+    /// Services or instance paths to write out, where a path ending in `/*` writes each child
+    /// to its own file; defaults to `Workspace/*` and `Lighting`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extract: Option<Vec<String>>,
+    /// The directory the map is written to, defaulting to `maps/<name>`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// The classes removed before anything is written; defaults to scripts and cameras
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strip: Option<Vec<String>>,
+}
+
+/// How often and how patiently failed Open Cloud requests are retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryPolicy {
+    /// The most times a request is made, counting the first
+    pub attempts: u32,
+    /// The delay before the first retry, doubled for every retry after it
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 4,
+            base_delay_ms: 500,
+            max_delay_ms: 30_000,
+        }
+    }
+}
+
+/// How many published builds are kept in `.rit/artifacts` for rollbacks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactRetention {
+    /// The builds kept for each place on each branch, newest first
+    pub keep: usize,
+    /// The most space all kept builds may take up, dropping the oldest first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_size_mb: Option<u64>,
+}
+
+impl Default for ArtifactRetention {
+    fn default() -> Self {
+        ArtifactRetention {
+            keep: 10,
+            max_size_mb: None,
+        }
+    }
+}
+
+/// The default DataStore used by the datastore commands
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DataStoreConfig {
+    pub name: String,
+    pub scope: String,
+}
+
+/// A DataStore that can be selected by alias with `--store`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DataStoreAlias {
+    pub name: String,
+    /// The scope to use, defaulting to `global`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Replacements for the name or scope on specific branches
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub branches: BTreeMap<String, DataStoreOverride>,
+}
+
+/// The parts of a DataStore alias that differ on a branch
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DataStoreOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+impl ConfigData {
+    pub fn read(reader: &mut Reader, json: &Value) -> Self {
+        let Some(root) = reader.object(json, "") else {
+            return ConfigData::default();
+        };
+        reader.known_keys(
+            root,
+            "",
+            &[
+                "$schema",
+                "deployment",
+                "import",
+                "datastore",
+                "datastores",
+                "gitBranches",
+                "tasks",
+                "hooks",
+                "retry",
+                "artifacts",
+            ],
+        );
+
+        ConfigData {
+            deployment: root
+                .get("deployment")
+                .and_then(|v| Deployment::read(reader, v, "/deployment")),
+            import: root
+                .get("import")
+                .and_then(|v| Import::read(reader, v, "/import")),
+            datastore: root
+                .get("datastore")
+                .and_then(|v| DataStoreConfig::read(reader, v, "/datastore")),
+            datastores: reader
+                .entries::<Value>(root.get("datastores"), "/datastores")
+                .into_iter()
+                .filter_map(|(alias, v)| {
+                    let pointer = Reader::pointer("/datastores", &alias);
+                    let store = DataStoreAlias::read(reader, &v, &pointer)?;
+                    Some((alias, store))
+                })
+                .collect(),
+            git_branches: root
+                .get("gitBranches")
+                .and_then(|v| GitBranches::read(reader, v, "/gitBranches"))
+                .unwrap_or_default(),
+            tasks: reader
+                .entries::<Value>(root.get("tasks"), "/tasks")
+                .into_iter()
+                .filter_map(|(name, v)| {
+                    let task = Task::read(reader, &v, &Reader::pointer("/tasks", &name))?;
+                    Some((name, task))
+                })
+                .collect(),
+            hooks: root
+                .get("hooks")
+                .and_then(|v| Hooks::read(reader, v, "/hooks"))
+                .unwrap_or_default(),
+            retry: root
+                .get("retry")
+                .and_then(|v| RetryPolicy::read(reader, v, "/retry"))
+                .unwrap_or_default(),
+            artifacts: root
+                .get("artifacts")
+                .and_then(|v| ArtifactRetention::read(reader, v, "/artifacts"))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl Deployment {
+    fn read(reader: &mut Reader, json: &Value, pointer: &str) -> Option<Self> {
+        let object = reader.object(json, pointer)?;
+        reader.known_keys(
+            object,
+            pointer,
+            &["universes", "places", "apiKeyEnv", "versionType"],
+        );
+        let universes =
+            reader.branch_entries(object.get("universes"), &format!("{pointer}/universes"));
+        let places = reader
+            .entries::<Value>(object.get("places"), &format!("{pointer}/places"))
+            .into_iter()
+            .filter_map(|(branch, v)| {
+                let branch_pointer = Reader::pointer(&format!("{pointer}/places"), &branch);
+                let places = reader.in_branch(&branch, |reader| {
+                    BranchPlaces::read(reader, &v, &branch_pointer)
+                })?;
+                Some((branch, places))
+            })
+            .collect();
+        let api_key_env =
+            reader.branch_entries(object.get("apiKeyEnv"), &format!("{pointer}/apiKeyEnv"));
+        let version_type =
+            reader.branch_entries(object.get("versionType"), &format!("{pointer}/versionType"));
+
+        Some(Deployment {
+            universes,
+            places,
+            api_key_env,
+            version_type,
+        })
+    }
+
+    /// Whether a branch has a universe or places of its own
+    pub fn has_branch(&self, branch: &str) -> bool {
+        self.universes.contains_key(branch) || self.places.contains_key(branch)
+    }
+
+    /// The branch followed by every branch it extends, nearest first
+    pub fn chain<'a>(&'a self, branch: &'a str) -> anyhow::Result<Vec<&'a str>> {
+        let mut chain = vec![branch];
+        let mut current = branch;
+
+        while let Some(parent) = self
+            .places
+            .get(current)
+            .and_then(|places| places.extends.as_deref())
+        {
+            if chain.contains(&parent) {
+                chain.push(parent);
+                anyhow::bail!(
+                    "Branch {} has an inheritance cycle: {}",
+                    branch,
+                    chain.join(" -> ")
+                );
+            }
+            if !self.has_branch(parent) {
+                anyhow::bail!("Branch {} extends unknown branch {}", current, parent);
+            }
+
+            chain.push(parent);
+            current = parent;
+        }
+
+        Ok(chain)
+    }
+
+    /// The universe id of a branch, falling back to the branches it extends
+    pub fn universe_id(&self, branch: &str) -> anyhow::Result<u64> {
+        self.chain(branch)?
+            .into_iter()
+            .find_map(|b| self.universes.get(b).copied())
+            .ok_or_else(|| anyhow::anyhow!("No universe id found for branch {}", branch))
+    }
+
+    /// The API key variable of a branch, or of the nearest branch it extends that sets one
+    pub fn api_key_env(&self, branch: &str) -> anyhow::Result<Option<&str>> {
+        Ok(self
+            .chain(branch)?
+            .into_iter()
+            .find_map(|b| self.api_key_env.get(b).map(|v| v.as_str())))
+    }
+
+    /// The default version type of a branch, or of the nearest branch it extends that sets one
+    pub fn version_type(&self, branch: &str) -> anyhow::Result<Option<VersionType>> {
+        Ok(self
+            .chain(branch)?
+            .into_iter()
+            .find_map(|b| self.version_type.get(b).copied()))
+    }
+
+    /// The places of a branch merged over the places of the branches it extends
+    pub fn resolve_places(&self, branch: &str) -> anyhow::Result<BTreeMap<String, PlaceConfig>> {
+        let chain = self.chain(branch)?;
+        if !chain.iter().any(|b| self.places.contains_key(*b)) {
+            anyhow::bail!("No places found for branch {}", branch);
+        }
+
+        let mut places = BTreeMap::new();
+        for b in chain.into_iter().rev() {
+            if let Some(branch_places) = self.places.get(b) {
+                places.extend(branch_places.places.clone());
+            }
+        }
+
+        Ok(places)
+    }
+}
+
+impl BranchPlaces {
+    fn read(reader: &mut Reader, json: &Value, pointer: &str) -> Option<Self> {
+        let object = reader.object(json, pointer)?;
+        let extends = reader.field(object, pointer, "extends");
+        let places = object
+            .iter()
+            .filter(|(key, _)| key.as_str() != "extends")
+            .filter_map(|(key, v)| {
+                let place = PlaceConfig::read(reader, v, &Reader::pointer(pointer, key))?;
+                Some((key.clone(), place))
+            })
+            .collect();
+
+        Some(BranchPlaces { extends, places })
+    }
+}
+
+impl PlaceConfig {
+    fn read(reader: &mut Reader, json: &Value, pointer: &str) -> Option<Self> {
+        let Value::Object(object) = json else {
+            let id = reader.value(json, pointer)?;
+            return Some(PlaceConfig::from_id(id));
+        };
+
+        reader.known_keys(
+            object,
+            pointer,
+            &["id", "project", "versionType", "skip", "output"],
+        );
+        let id = reader.required(object, pointer, "id");
+        let project = reader.field(object, pointer, "project");
+        let version_type = reader.field(object, pointer, "versionType");
+        let skip = reader.field(object, pointer, "skip");
+        let output = reader.field(object, pointer, "output");
+
+        Some(PlaceConfig {
+            id: id?,
+            project,
+            version_type,
+            skip: skip.unwrap_or_default(),
+            output,
+        })
+    }
+
+    pub fn from_id(id: u64) -> Self {
+        PlaceConfig {
+            id,
+            project: None,
+            version_type: None,
+            skip: false,
+            output: None,
+        }
+    }
+
+    pub fn project_file(&self, name: &str) -> String {
+        self.project
+            .clone()
+            .unwrap_or_else(|| format!("{name}.project.json"))
+    }
+}
+
+impl OutputFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Rbxl => "rbxl",
+            OutputFormat::Rbxlx => "rbxlx",
+        }
+    }
+
+    /// The format of a place file, going by its extension
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("rbxlx") => OutputFormat::Rbxlx,
+            _ => OutputFormat::Rbxl,
+        }
+    }
+
+    /// The content type Open Cloud expects a place file of this format to be uploaded with
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            OutputFormat::Rbxl => "application/octet-stream",
+            OutputFormat::Rbxlx => "application/xml",
+        }
+    }
+}
+
+impl Import {
+    fn read(reader: &mut Reader, json: &Value, pointer: &str) -> Option<Self> {
+        let object = reader.object(json, pointer)?;
+        reader.known_keys(object, pointer, &["assetsPlaceId", "places"]);
+        let assets_place_id = reader.field(object, pointer, "assetsPlaceId");
+        let places_pointer = format!("{pointer}/places");
+        let places = match object.get("places") {
+            Some(json) => reader
+                .object(json, &places_pointer)
+                .into_iter()
+                .flatten()
+                .filter_map(|(name, v)| {
+                    let place_pointer = Reader::pointer(&places_pointer, name);
+                    let place = ImportPlace::read(reader, v, &place_pointer)?;
+                    Some((name.clone(), place))
+                })
+                .collect(),
+            None => BTreeMap::new(),
+        };
+
+        Some(Import {
+            assets_place_id,
+            places,
+        })
+    }
+}
+
+impl ImportPlace {
+    fn read(reader: &mut Reader, json: &Value, pointer: &str) -> Option<Self> {
+        let Value::Object(object) = json else {
+            let id = reader.value(json, pointer)?;
+            return Some(ImportPlace {
+                id,
+                extract: None,
+                output: None,
+                strip: None,
+            });
+        };
+
+        reader.known_keys(object, pointer, &["id", "extract", "output", "strip"]);
+        let id = reader.required(object, pointer, "id");
+        let extract = reader.field(object, pointer, "extract");
+        let output = reader.field(object, pointer, "output");
+        let strip = reader.field(object, pointer, "strip");
+
+        Some(ImportPlace {
+            id: id?,
+            extract,
+            output,
+            strip,
+        })
+    }
+}
+
+impl RetryPolicy {
+    fn read(reader: &mut Reader, json: &Value, pointer: &str) -> Option<Self> {
+        let object = reader.object(json, pointer)?;
+        reader.known_keys(object, pointer, &["attempts", "baseDelayMs", "maxDelayMs"]);
+        let defaults = RetryPolicy::default();
+
+        Some(RetryPolicy {
+            attempts: reader
+                .field(object, pointer, "attempts")
+                .unwrap_or(defaults.attempts),
+            base_delay_ms: reader
+                .field(object, pointer, "baseDelayMs")
+                .unwrap_or(defaults.base_delay_ms),
+            max_delay_ms: reader
+                .field(object, pointer, "maxDelayMs")
+                .unwrap_or(defaults.max_delay_ms),
+        })
+    }
+}
+
+impl ArtifactRetention {
+    fn read(reader: &mut Reader, json: &Value, pointer: &str) -> Option<Self> {
+        let object = reader.object(json, pointer)?;
+        reader.known_keys(object, pointer, &["keep", "maxSizeMb"]);
+
+        Some(ArtifactRetention {
+            keep: reader
+                .field(object, pointer, "keep")
+                .unwrap_or(ArtifactRetention::default().keep),
+            max_size_mb: reader.field(object, pointer, "maxSizeMb"),
+        })
+    }
+}
+
+impl DataStoreConfig {
+    fn read(reader: &mut Reader, json: &Value, pointer: &str) -> Option<Self> {
+        let object = reader.object(json, pointer)?;
+        reader.known_keys(object, pointer, &["name", "scope"]);
+        let name = reader.required(object, pointer, "name");
+        let scope = reader.required(object, pointer, "scope");
+
+        Some(DataStoreConfig {
+            name: name?,
+            scope: scope?,
+        })
+    }
+}
+
+impl DataStoreAlias {
+    fn read(reader: &mut Reader, json: &Value, pointer: &str) -> Option<Self> {
+        let object = reader.object(json, pointer)?;
+        reader.known_keys(object, pointer, &["name", "scope", "branches"]);
+        let name = reader.required(object, pointer, "name");
+        let scope = reader.field(object, pointer, "scope");
+        let branches_pointer = format!("{pointer}/branches");
+        let branches = reader
+            .entries::<Value>(object.get("branches"), &branches_pointer)
+            .into_iter()
+            .filter_map(|(branch, v)| {
+                let pointer = Reader::pointer(&branches_pointer, &branch);
+                let object = reader.object(&v, &pointer)?;
+                reader.known_keys(object, &pointer, &["name", "scope"]);
+                let (name, scope) = reader.in_branch(&branch, |reader| {
+                    let name = reader.field(object, &pointer, "name");
+                    let scope = reader.field(object, &pointer, "scope");
+                    (name, scope)
+                });
+                Some((branch, DataStoreOverride { name, scope }))
+            })
+            .collect();
+
+        Some(DataStoreAlias {
+            name: name?,
+            scope,
+            branches,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn deployment(places: Value) -> Deployment {
+        let mut reader = Reader::new();
+        let json = json!({ "universes": { "main": 1 }, "places": places });
+        let deployment = Deployment::read(&mut reader, &json, "/deployment").unwrap();
+        assert!(reader.issues.is_empty(), "{:?}", reader.issues);
+        deployment
+    }
+
+    #[test]
+    fn chain_follows_extends() {
+        let deployment = deployment(json!({
+            "main": { "default": 1 },
+            "staging": { "extends": "main", "lobby": 2 },
+            "qa": { "extends": "staging" }
+        }));
+
+        assert_eq!(deployment.chain("qa").unwrap(), ["qa", "staging", "main"]);
+        assert_eq!(deployment.universe_id("qa").unwrap(), 1);
+        let places = deployment.resolve_places("qa").unwrap();
+        assert_eq!(places.keys().collect::<Vec<_>>(), ["default", "lobby"]);
+    }
+
+    #[test]
+    fn chain_detects_cycles() {
+        let deployment = deployment(json!({
+            "a": { "extends": "b" },
+            "b": { "extends": "c" },
+            "c": { "extends": "a" },
+            "d": { "extends": "d" }
+        }));
+
+        let err = deployment.chain("a").unwrap_err().to_string();
+        assert!(err.contains("a -> b -> c -> a"), "{err}");
+        let err = deployment.chain("d").unwrap_err().to_string();
+        assert!(err.contains("d -> d"), "{err}");
+    }
+
+    #[test]
+    fn reports_unknown_keys() {
+        let mut reader = Reader::new();
+        let json = json!({
+            "$schema": "./config.schema.json",
+            "deployment": {
+                "universes": { "main": 1 },
+                "places": { "main": { "lobby": { "id": 5, "versiontype": "Saved" } } }
+            },
+            "retyr": {}
+        });
+        ConfigData::read(&mut reader, &json);
+
+        let pointers: Vec<&str> = reader.issues.iter().map(|i| i.pointer.as_str()).collect();
+        assert_eq!(
+            pointers,
+            ["/retyr", "/deployment/places/main/lobby/versiontype"]
+        );
+    }
+
+    #[test]
+    fn chain_rejects_unknown_branches() {
+        let deployment = deployment(json!({ "qa": { "extends": "missing" } }));
+
+        let err = deployment.chain("qa").unwrap_err().to_string();
+        assert_eq!(err, "Branch qa extends unknown branch missing");
+    }
+}
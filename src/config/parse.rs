@@ -0,0 +1,365 @@
+use super::interpolate::{interpolate, InterpolateError};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A single problem found in a config file
+#[derive(Debug, Clone)]
+pub struct Issue {
+    /// JSON pointer to the offending value
+    pub pointer: String,
+    pub message: String,
+    /// The branch whose value uses an environment variable that is not set; such issues
+    /// only matter when that branch is used, as its variables may come from its own env file
+    pub branch: Option<String>,
+}
+
+impl Issue {
+    /// Whether the issue matters to a command using the given branches
+    pub fn applies_to(&self, branches: &[&str]) -> bool {
+        self.branch
+            .as_deref()
+            .is_none_or(|branch| branches.contains(&branch))
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() {
+            "/"
+        } else {
+            &self.pointer
+        };
+        write!(f, "{}: {}", pointer, self.message)
+    }
+}
+
+/// Every problem found while reading a config file
+#[derive(Debug)]
+pub struct Issues {
+    pub path: String,
+    pub issues: Vec<Issue>,
+}
+
+impl fmt::Display for Issues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} has {} problem{}:",
+            self.path,
+            self.issues.len(),
+            if self.issues.len() == 1 { "" } else { "s" }
+        )?;
+        for issue in &self.issues {
+            write!(f, "\n  {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Issues {}
+
+/// Reads typed values out of raw JSON, collecting issues instead of stopping at the first one
+#[derive(Debug, Default)]
+pub struct Reader {
+    pub issues: Vec<Issue>,
+    /// The branch whose values are being read
+    branch: Option<String>,
+    /// Takes strings with placeholders to be valid instead of expanding them
+    skip_placeholders: bool,
+}
+
+impl Reader {
+    pub fn new() -> Self {
+        Reader::default()
+    }
+
+    /// A reader that takes every placeholder to expand to a valid value, for checking a file
+    /// without the environment it would be expanded from
+    pub fn without_placeholders() -> Self {
+        Reader {
+            skip_placeholders: true,
+            ..Reader::default()
+        }
+    }
+
+    /// Appends an escaped key to a JSON pointer
+    pub fn pointer(parent: &str, key: &str) -> String {
+        format!("{}/{}", parent, key.replace('~', "~0").replace('/', "~1"))
+    }
+
+    pub fn issue(&mut self, pointer: &str, message: impl Into<String>) {
+        self.issues.push(Issue {
+            pointer: pointer.to_string(),
+            message: message.into(),
+            branch: None,
+        });
+    }
+
+    /// Reads the values of a single branch, so unset variables in them are charged to it
+    pub fn in_branch<R>(&mut self, branch: &str, read: impl FnOnce(&mut Reader) -> R) -> R {
+        let outer = self.branch.replace(branch.to_string());
+        let result = read(self);
+        self.branch = outer;
+        result
+    }
+
+    /// Reads a typed value, expanding placeholders unless the value is read as raw JSON
+    pub fn value<T: DeserializeOwned + 'static>(
+        &mut self,
+        json: &Value,
+        pointer: &str,
+    ) -> Option<T> {
+        // Raw JSON is expanded later, when its own fields are read
+        if TypeId::of::<T>() != TypeId::of::<Value>() {
+            if let Value::String(text) = json {
+                if text.contains("${") {
+                    if self.skip_placeholders {
+                        return stand_in(text);
+                    }
+                    return self.interpolated(text, pointer);
+                }
+            }
+        }
+
+        match T::deserialize(json) {
+            Ok(v) => Some(v),
+            Err(err) => {
+                self.issue(pointer, err.to_string());
+                None
+            }
+        }
+    }
+
+    /// Reads a string containing environment variable placeholders
+    fn interpolated<T: DeserializeOwned>(&mut self, text: &str, pointer: &str) -> Option<T> {
+        let expanded = match interpolate(text) {
+            Ok(v) => v,
+            Err(err) => {
+                let branch = match err {
+                    InterpolateError::Unset(_) => self.branch.clone(),
+                    InterpolateError::Invalid(_) => None,
+                };
+                self.issues.push(Issue {
+                    pointer: pointer.to_string(),
+                    message: err.to_string(),
+                    branch,
+                });
+                return None;
+            }
+        };
+
+        let err = match T::deserialize(&Value::String(expanded.clone())) {
+            Ok(v) => return Some(v),
+            Err(err) => err,
+        };
+
+        // A placeholder can also stand in for a number, such as a universe id
+        if let Ok(number) = expanded.parse::<serde_json::Number>() {
+            if let Ok(v) = T::deserialize(&Value::Number(number)) {
+                return Some(v);
+            }
+        }
+
+        self.issue(pointer, format!("{err} (expanded from {text:?})"));
+        None
+    }
+
+    pub fn object<'a>(&mut self, json: &'a Value, pointer: &str) -> Option<&'a Map<String, Value>> {
+        match json {
+            Value::Object(map) => Some(map),
+            other => {
+                self.issue(
+                    pointer,
+                    format!("invalid type: {}, expected an object", describe(other)),
+                );
+                None
+            }
+        }
+    }
+
+    /// Records an issue for each key of an object that is not one of `known`, so a misspelt
+    /// setting is reported instead of ignored
+    pub fn known_keys(&mut self, object: &Map<String, Value>, pointer: &str, known: &[&str]) {
+        for key in object.keys() {
+            if !known.contains(&key.as_str()) {
+                self.issue(
+                    &Reader::pointer(pointer, key),
+                    format!("unknown key, expected one of {}", known.join(", ")),
+                );
+            }
+        }
+    }
+
+    /// Reads an optional field of an object
+    pub fn field<T: DeserializeOwned + 'static>(
+        &mut self,
+        object: &Map<String, Value>,
+        pointer: &str,
+        key: &str,
+    ) -> Option<T> {
+        let json = object.get(key)?;
+        self.value(json, &Reader::pointer(pointer, key))
+    }
+
+    /// Reads a field of an object, recording an issue if it is missing
+    pub fn required<T: DeserializeOwned + 'static>(
+        &mut self,
+        object: &Map<String, Value>,
+        pointer: &str,
+        key: &str,
+    ) -> Option<T> {
+        if !object.contains_key(key) {
+            self.issue(pointer, format!("missing field `{key}`"));
+            return None;
+        }
+        self.field(object, pointer, key)
+    }
+
+    /// Reads every entry of an object, skipping (and recording) the ones that fail
+    pub fn entries<T: DeserializeOwned + 'static>(
+        &mut self,
+        json: Option<&Value>,
+        pointer: &str,
+    ) -> BTreeMap<String, T> {
+        let Some(object) = json.and_then(|v| self.object(v, pointer)) else {
+            return BTreeMap::new();
+        };
+
+        object
+            .iter()
+            .filter_map(|(key, v)| {
+                let value = self.value(v, &Reader::pointer(pointer, key))?;
+                Some((key.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Reads every entry of an object keyed by branch name
+    pub fn branch_entries<T: DeserializeOwned + 'static>(
+        &mut self,
+        json: Option<&Value>,
+        pointer: &str,
+    ) -> BTreeMap<String, T> {
+        let Some(object) = json.and_then(|v| self.object(v, pointer)) else {
+            return BTreeMap::new();
+        };
+
+        object
+            .iter()
+            .filter_map(|(branch, v)| {
+                let value = self.in_branch(branch, |reader| {
+                    reader.value(v, &Reader::pointer(pointer, branch))
+                })?;
+                Some((branch.clone(), value))
+            })
+            .collect()
+    }
+}
+
+/// A value for a placeholder that is not expanded: the text itself where a string is
+/// expected, or else the lowest valid id
+fn stand_in<T: DeserializeOwned>(text: &str) -> Option<T> {
+    T::deserialize(&Value::String(text.to_string()))
+        .or_else(|_| T::deserialize(&Value::from(1u64)))
+        .ok()
+}
+
+fn describe(json: &Value) -> String {
+    match json {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => format!("boolean `{b}`"),
+        Value::Number(n) => format!("number {n}"),
+        Value::String(s) => format!("string {s:?}"),
+        Value::Array(_) => "array".to_string(),
+        Value::Object(_) => "object".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_typed_values() {
+        let mut reader = Reader::new();
+        assert_eq!(reader.value::<u64>(&json!(5), "/id"), Some(5));
+        assert_eq!(reader.value::<u64>(&json!("five"), "/id"), None);
+
+        assert_eq!(reader.issues.len(), 1);
+        assert_eq!(reader.issues[0].pointer, "/id");
+    }
+
+    #[test]
+    fn coerces_expanded_numbers() {
+        std::env::set_var("RIT_TEST_PARSE_ID", "12");
+        let mut reader = Reader::new();
+
+        assert_eq!(
+            reader.value::<u64>(&json!("${RIT_TEST_PARSE_ID}"), "/id"),
+            Some(12)
+        );
+        assert_eq!(
+            reader.value::<String>(&json!("${RIT_TEST_PARSE_ID}"), "/name"),
+            Some("12".to_string())
+        );
+        assert_eq!(
+            reader.value::<u64>(&json!("${RIT_TEST_PARSE_MISSING:-3}"), "/id"),
+            Some(3)
+        );
+        assert!(reader.issues.is_empty());
+
+        assert_eq!(
+            reader.value::<u64>(&json!("${RIT_TEST_PARSE_MISSING:-x}"), "/id"),
+            None
+        );
+        assert!(reader.issues[0].message.contains("expanded from"));
+    }
+
+    #[test]
+    fn leaves_raw_values_unexpanded() {
+        let mut reader = Reader::new();
+        let json = json!("${RIT_TEST_PARSE_MISSING}");
+
+        assert_eq!(reader.value::<Value>(&json, "/raw"), Some(json.clone()));
+        assert!(reader.issues.is_empty());
+    }
+
+    #[test]
+    fn charges_unset_variables_to_their_branch() {
+        let mut reader = Reader::new();
+        let json = json!("${RIT_TEST_PARSE_MISSING}");
+
+        reader.value::<u64>(&json, "/shared");
+        reader.in_branch("test", |reader| reader.value::<u64>(&json, "/test"));
+        reader.value::<u64>(&json!("${RIT_TEST_PARSE_MISSING"), "/broken");
+        reader.in_branch("test", |reader| {
+            reader.value::<u64>(&json!("${}"), "/empty")
+        });
+
+        let branches: Vec<Option<&str>> =
+            reader.issues.iter().map(|i| i.branch.as_deref()).collect();
+        assert_eq!(branches, [None, Some("test"), None, None]);
+        assert!(reader.issues[1].applies_to(&["test", "main"]));
+        assert!(!reader.issues[1].applies_to(&["main"]));
+        assert!(reader.issues[0].applies_to(&[]));
+    }
+
+    #[test]
+    fn skips_placeholders_when_asked() {
+        let mut reader = Reader::without_placeholders();
+        assert_eq!(
+            reader.value::<u64>(&json!("${RIT_TEST_PARSE_MISSING}"), "/id"),
+            Some(1)
+        );
+        assert_eq!(
+            reader.value::<String>(&json!("${RIT_TEST_PARSE_MISSING}"), "/name"),
+            Some("${RIT_TEST_PARSE_MISSING}".to_string())
+        );
+        assert_eq!(reader.value::<u64>(&json!(4), "/id"), Some(4));
+        assert!(reader.issues.is_empty());
+    }
+}
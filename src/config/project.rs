@@ -0,0 +1,63 @@
+use super::format::{find_config, CONFIG_FILES};
+use anyhow::Context;
+use std::path::{Path, PathBuf};
+
+/// Where a project lives on disk; relative paths are resolved against its root
+#[derive(Debug, Clone)]
+pub struct Project {
+    /// The member name, for projects that are part of a workspace
+    pub name: Option<String>,
+    pub root: PathBuf,
+    pub config_path: Option<PathBuf>,
+}
+
+impl Project {
+    /// Uses the given config file, or walks up from the current directory to find one
+    pub fn locate(config_path: Option<&Path>) -> anyhow::Result<Self> {
+        let current_dir = std::env::current_dir()?;
+
+        if let Some(path) = config_path {
+            let path = current_dir.join(path);
+            if !path.is_file() {
+                anyhow::bail!("Config file {} does not exist", path.display());
+            }
+            let path = path.canonicalize()?;
+
+            return Ok(Project {
+                name: None,
+                root: path.parent().unwrap().to_path_buf(),
+                config_path: Some(path),
+            });
+        }
+
+        for dir in current_dir.ancestors() {
+            if let Some(path) = find_config(dir)? {
+                return Ok(Project {
+                    name: None,
+                    root: dir.to_path_buf(),
+                    config_path: Some(path),
+                });
+            }
+        }
+
+        Ok(Project {
+            name: None,
+            root: current_dir,
+            config_path: None,
+        })
+    }
+
+    pub fn config_path(&self) -> anyhow::Result<&Path> {
+        self.config_path.as_deref().with_context(|| {
+            format!(
+                "No config file found in {} or any parent directory; expected one of {}",
+                self.root.display(),
+                CONFIG_FILES.join(", ")
+            )
+        })
+    }
+
+    pub fn path(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.root.join(relative)
+    }
+}
@@ -0,0 +1,295 @@
+use serde_json::{json, Value};
+
+/// JSON Schema describing config.json, for editor validation and autocompletion
+pub fn schema() -> Value {
+    let id = json!({
+        "anyOf": [
+            {
+                "type": "integer",
+                "minimum": 1
+            },
+            {
+                "description": "An environment variable, such as ${UNIVERSE_ID} or ${UNIVERSE_ID:-123}",
+                "type": "string",
+                "pattern": "^\\$\\{[^}]+\\}$"
+            }
+        ]
+    });
+
+    let version_type = json!({
+        "enum": ["Saved", "Published"]
+    });
+
+    let place = json!({
+        "anyOf": [
+            id,
+            {
+                "type": "object",
+                "properties": {
+                    "id": id,
+                    "project": {
+                        "description": "The project file to build, defaulting to <name>.project.json",
+                        "type": "string"
+                    },
+                    "versionType": version_type,
+                    "skip": {
+                        "description": "Leave this place out of deploys",
+                        "type": "boolean"
+                    },
+                    "output": {
+                        "description": "The file format to build the place to",
+                        "enum": ["rbxl", "rbxlx"]
+                    }
+                },
+                "required": ["id"],
+                "additionalProperties": false
+            }
+        ]
+    });
+
+    let commands = json!({
+        "anyOf": [
+            {
+                "type": "string"
+            },
+            {
+                "type": "array",
+                "items": {
+                    "type": "string"
+                }
+            }
+        ]
+    });
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "rit config",
+        "type": "object",
+        "properties": {
+            "$schema": {
+                "type": "string"
+            },
+            "deployment": {
+                "description": "Universes and places to deploy to, keyed by branch",
+                "type": "object",
+                "properties": {
+                    "universes": {
+                        "description": "The universe id of each branch",
+                        "type": "object",
+                        "additionalProperties": id
+                    },
+                    "places": {
+                        "description": "The places of each branch, keyed by project name",
+                        "type": "object",
+                        "additionalProperties": {
+                            "type": "object",
+                            "properties": {
+                                "extends": {
+                                    "description": "A branch to inherit places and the universe from",
+                                    "type": "string"
+                                }
+                            },
+                            "additionalProperties": place
+                        }
+                    },
+                    "apiKeyEnv": {
+                        "description": "The environment variable holding the Open Cloud key of each branch, defaulting to OPENCLOUD_KEY",
+                        "type": "object",
+                        "additionalProperties": {
+                            "type": "string"
+                        }
+                    },
+                    "versionType": {
+                        "description": "The version type of each branch's places that do not set their own, defaulting to Published",
+                        "type": "object",
+                        "additionalProperties": version_type
+                    }
+                },
+                "additionalProperties": false
+            },
+            "import": {
+                "description": "Places that maps and assets are imported from",
+                "type": "object",
+                "properties": {
+                    "assetsPlaceId": id,
+                    "places": {
+                        "description": "The place each map is imported from, keyed by map name",
+                        "type": "object",
+                        "additionalProperties": {
+                            "anyOf": [
+                                id,
+                                {
+                                    "type": "object",
+                                    "properties": {
+                                        "id": id,
+                                        "extract": {
+                                            "description": "Services or instance paths to write out; a path ending in /* writes each child to its own file. Defaults to Workspace/* and Lighting",
+                                            "type": "array",
+                                            "items": {
+                                                "type": "string"
+                                            }
+                                        },
+                                        "output": {
+                                            "description": "The directory the map is written to, defaulting to maps/<name>",
+                                            "type": "string"
+                                        },
+                                        "strip": {
+                                            "description": "The classes removed before anything is written, defaulting to scripts and cameras",
+                                            "type": "array",
+                                            "items": {
+                                                "type": "string"
+                                            }
+                                        }
+                                    },
+                                    "required": ["id"],
+                                    "additionalProperties": false
+                                }
+                            ]
+                        }
+                    }
+                },
+                "additionalProperties": false
+            },
+            "datastore": {
+                "description": "The default DataStore used by the datastore commands",
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string"
+                    },
+                    "scope": {
+                        "type": "string"
+                    }
+                },
+                "required": ["name", "scope"],
+                "additionalProperties": false
+            },
+            "gitBranches": {
+                "description": "The branch to use when -b is not given, keyed by git branch or glob pattern",
+                "type": "object",
+                "additionalProperties": {
+                    "type": "string"
+                }
+            },
+            "tasks": {
+                "description": "Commands run with rit task, keyed by task name",
+                "type": "object",
+                "additionalProperties": {
+                    "type": "object",
+                    "properties": {
+                        "dependsOn": {
+                            "description": "Tasks to run first",
+                            "type": "array",
+                            "items": {
+                                "type": "string"
+                            }
+                        },
+                        "env": {
+                            "description": "Environment variables set for every step",
+                            "type": "object",
+                            "additionalProperties": {
+                                "type": "string"
+                            }
+                        },
+                        "steps": {
+                            "description": "Shell commands to run in order; a step starting with rit runs this same rit",
+                            "type": "array",
+                            "items": {
+                                "type": "string"
+                            }
+                        }
+                    },
+                    "additionalProperties": false
+                }
+            },
+            "hooks": {
+                "description": "Shell commands run around builds, deploys and imports, with RIT_* variables describing the operation",
+                "type": "object",
+                "properties": {
+                    "preBuild": commands,
+                    "postBuild": commands,
+                    "preDeploy": commands,
+                    "postDeploy": commands,
+                    "postPublishPlace": commands,
+                    "preImport": commands,
+                    "postImport": commands
+                },
+                "additionalProperties": false
+            },
+            "retry": {
+                "description": "How failed Open Cloud requests are retried, with exponential backoff and jitter",
+                "type": "object",
+                "properties": {
+                    "attempts": {
+                        "description": "The most times a request is made, counting the first; defaults to 4",
+                        "type": "integer",
+                        "minimum": 1
+                    },
+                    "baseDelayMs": {
+                        "description": "The delay before the first retry, doubled for every retry after it; defaults to 500",
+                        "type": "integer",
+                        "minimum": 0
+                    },
+                    "maxDelayMs": {
+                        "description": "The longest delay between retries, unless the server asks for longer with Retry-After; defaults to 30000",
+                        "type": "integer",
+                        "minimum": 0
+                    }
+                },
+                "additionalProperties": false
+            },
+            "artifacts": {
+                "description": "How many published builds are kept in .rit/artifacts for rit rollback",
+                "type": "object",
+                "properties": {
+                    "keep": {
+                        "description": "The builds kept for each place on each branch; defaults to 10",
+                        "type": "integer",
+                        "minimum": 1
+                    },
+                    "maxSizeMb": {
+                        "description": "The most space all kept builds may take up, dropping the oldest first",
+                        "type": "integer",
+                        "minimum": 0
+                    }
+                },
+                "additionalProperties": false
+            },
+            "datastores": {
+                "description": "DataStores that can be selected with --store, keyed by alias",
+                "type": "object",
+                "additionalProperties": {
+                    "type": "object",
+                    "properties": {
+                        "name": {
+                            "type": "string"
+                        },
+                        "scope": {
+                            "description": "Defaults to global",
+                            "type": "string"
+                        },
+                        "branches": {
+                            "description": "Replacements for the name or scope on specific branches",
+                            "type": "object",
+                            "additionalProperties": {
+                                "type": "object",
+                                "properties": {
+                                    "name": {
+                                        "type": "string"
+                                    },
+                                    "scope": {
+                                        "type": "string"
+                                    }
+                                },
+                                "additionalProperties": false
+                            }
+                        }
+                    },
+                    "required": ["name"],
+                    "additionalProperties": false
+                }
+            }
+        },
+        "additionalProperties": false
+    })
+}
@@ -0,0 +1,79 @@
+use super::parse::Reader;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// A named list of commands run with `rit task`, after the tasks it depends on
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Environment variables set for every step of the task
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Shell commands; a step starting with `rit` runs this same rit executable
+    #[serde(default)]
+    pub steps: Vec<String>,
+}
+
+impl Task {
+    pub fn read(reader: &mut Reader, json: &Value, pointer: &str) -> Option<Self> {
+        let object = reader.object(json, pointer)?;
+        reader.known_keys(object, pointer, &["dependsOn", "env", "steps"]);
+        let depends_on = reader.field(object, pointer, "dependsOn");
+        let env = reader.entries(object.get("env"), &format!("{pointer}/env"));
+        let steps = reader.field(object, pointer, "steps");
+
+        Some(Task {
+            depends_on: depends_on.unwrap_or_default(),
+            env,
+            steps: steps.unwrap_or_default(),
+        })
+    }
+}
+
+/// The tasks to run for a task, each after the tasks it depends on and each only once
+pub fn task_order<'a>(
+    tasks: &'a BTreeMap<String, Task>,
+    name: &'a str,
+) -> anyhow::Result<Vec<&'a str>> {
+    let mut order = Vec::new();
+    visit(tasks, name, &mut Vec::new(), &mut order)?;
+    Ok(order)
+}
+
+fn visit<'a>(
+    tasks: &'a BTreeMap<String, Task>,
+    name: &'a str,
+    path: &mut Vec<&'a str>,
+    order: &mut Vec<&'a str>,
+) -> anyhow::Result<()> {
+    if order.contains(&name) {
+        return Ok(());
+    }
+    if path.contains(&name) {
+        path.push(name);
+        anyhow::bail!(
+            "Task {} has a dependency cycle: {}",
+            path[0],
+            path.join(" -> ")
+        );
+    }
+
+    let Some(task) = tasks.get(name) else {
+        match path.last() {
+            Some(parent) => anyhow::bail!("Task {} depends on unknown task {}", parent, name),
+            None => anyhow::bail!("No task named {}", name),
+        }
+    };
+
+    path.push(name);
+    for dependency in &task.depends_on {
+        visit(tasks, dependency, path, order)?;
+    }
+    path.pop();
+
+    order.push(name);
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use super::{task_order, ConfigData, Issue, Reader};
+use std::path::Path;
+
+/// Checks a config for problems that are not caught by its types
+pub fn validate(data: &ConfigData, root: &Path) -> Vec<Issue> {
+    let mut reader = Reader::new();
+
+    if let Some(deployment) = &data.deployment {
+        for (branch, universe_id) in &deployment.universes {
+            let pointer = Reader::pointer("/deployment/universes", branch);
+            positive_id(&mut reader, &pointer, *universe_id);
+
+            if !deployment.places.contains_key(branch) {
+                reader.issue(
+                    &pointer,
+                    format!("branch \"{branch}\" has no matching entry in /deployment/places"),
+                );
+            }
+        }
+
+        for (branch, places) in &deployment.places {
+            let branch_pointer = Reader::pointer("/deployment/places", branch);
+
+            if let Err(err) = deployment.chain(branch) {
+                reader.issue(
+                    &Reader::pointer(&branch_pointer, "extends"),
+                    err.to_string(),
+                );
+            } else if deployment.universe_id(branch).is_err() {
+                reader.issue(
+                    &branch_pointer,
+                    format!("branch \"{branch}\" has no matching entry in /deployment/universes"),
+                );
+            }
+
+            for (place_name, place) in &places.places {
+                let pointer = Reader::pointer(&branch_pointer, place_name);
+                positive_id(&mut reader, &pointer, place.id);
+
+                let project_file = place.project_file(place_name);
+                if !place.skip && !root.join(&project_file).is_file() {
+                    reader.issue(
+                        &pointer,
+                        format!("project file {project_file} does not exist"),
+                    );
+                }
+            }
+        }
+
+        for branch in deployment.api_key_env.keys() {
+            if !deployment.has_branch(branch) {
+                reader.issue(
+                    &Reader::pointer("/deployment/apiKeyEnv", branch),
+                    format!("branch \"{branch}\" is not a deployment branch"),
+                );
+            }
+        }
+
+        for branch in deployment.version_type.keys() {
+            if !deployment.has_branch(branch) {
+                reader.issue(
+                    &Reader::pointer("/deployment/versionType", branch),
+                    format!("branch \"{branch}\" is not a deployment branch"),
+                );
+            }
+        }
+    }
+
+    for (pattern, branch) in &data.git_branches.0 {
+        let pointer = Reader::pointer("/gitBranches", pattern);
+        if let Err(err) = glob::Pattern::new(pattern) {
+            reader.issue(&pointer, format!("invalid pattern: {err}"));
+        }
+
+        let known = data
+            .deployment
+            .as_ref()
+            .is_some_and(|d| d.has_branch(branch));
+        if !known {
+            reader.issue(
+                &pointer,
+                format!("branch \"{branch}\" is not a deployment branch"),
+            );
+        }
+    }
+
+    for (alias, store) in &data.datastores {
+        let branches_pointer = format!("{}/branches", Reader::pointer("/datastores", alias));
+        for branch in store.branches.keys() {
+            let known = data
+                .deployment
+                .as_ref()
+                .is_some_and(|d| d.has_branch(branch));
+            if !known {
+                reader.issue(
+                    &Reader::pointer(&branches_pointer, branch),
+                    format!("branch \"{branch}\" is not a deployment branch"),
+                );
+            }
+        }
+    }
+
+    for (name, task) in &data.tasks {
+        let pointer = Reader::pointer("/tasks", name);
+        if let Err(err) = task_order(&data.tasks, name) {
+            reader.issue(&format!("{pointer}/dependsOn"), err.to_string());
+        }
+        if task.steps.is_empty() && task.depends_on.is_empty() {
+            reader.issue(&pointer, "task has no steps and depends on no tasks");
+        }
+    }
+
+    if let Some(import) = &data.import {
+        if let Some(assets_place_id) = import.assets_place_id {
+            positive_id(&mut reader, "/import/assetsPlaceId", assets_place_id);
+        }
+
+        for (place_name, place) in &import.places {
+            let pointer = Reader::pointer("/import/places", place_name);
+            positive_id(&mut reader, &pointer, place.id);
+
+            for (i, path) in place.extract.iter().flatten().enumerate() {
+                let base = path.strip_suffix("/*").unwrap_or(path);
+                if base.is_empty() || base.split('/').any(|segment| segment.is_empty()) {
+                    reader.issue(
+                        &format!("{pointer}/extract/{i}"),
+                        format!("{path:?} is not an instance path such as Workspace/* or ReplicatedStorage/Maps"),
+                    );
+                }
+            }
+        }
+    }
+
+    if data.retry.attempts == 0 {
+        reader.issue(
+            "/retry/attempts",
+            "invalid value: 0, expected at least one attempt",
+        );
+    }
+    if data.retry.base_delay_ms > data.retry.max_delay_ms {
+        reader.issue(
+            "/retry/baseDelayMs",
+            format!(
+                "the base delay is longer than the maximum delay of {}ms",
+                data.retry.max_delay_ms
+            ),
+        );
+    }
+
+    if data.artifacts.keep == 0 {
+        reader.issue(
+            "/artifacts/keep",
+            "invalid value: 0, expected at least one build so a rollback has something to restore",
+        );
+    }
+
+    reader.issues
+}
+
+fn positive_id(reader: &mut Reader, pointer: &str, id: u64) {
+    if id == 0 {
+        reader.issue(pointer, "invalid value: 0, expected a positive integer id");
+    }
+}
@@ -0,0 +1,116 @@
+use super::format::{find_config, CONFIG_FILES};
+use super::{read_json, Project};
+use anyhow::Context;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// The file names a workspace can be loaded from, in order of preference
+pub const WORKSPACE_FILES: [&str; 4] = [
+    "rit-workspace.json",
+    "rit-workspace.toml",
+    "rit-workspace.yaml",
+    "rit-workspace.yml",
+];
+
+#[derive(Debug, Deserialize)]
+struct WorkspaceFile {
+    members: Vec<String>,
+}
+
+/// Several projects under one root, each with its own config
+#[derive(Debug)]
+pub struct Workspace {
+    pub path: PathBuf,
+    pub members: Vec<Project>,
+}
+
+impl Workspace {
+    /// Walks up from a directory to find a workspace file
+    pub fn locate(start: &Path) -> anyhow::Result<Option<Self>> {
+        for dir in start.ancestors() {
+            let found: Vec<PathBuf> = WORKSPACE_FILES
+                .iter()
+                .map(|name| dir.join(name))
+                .filter(|path| path.is_file())
+                .collect();
+
+            match found.as_slice() {
+                [] => continue,
+                [path] => return Workspace::read(path).map(Some),
+                _ => anyhow::bail!(
+                    "Found more than one workspace file in {}; keep only one",
+                    dir.display()
+                ),
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let display = path.display().to_string();
+        let file: WorkspaceFile = serde_json::from_value(read_json(&display)?)
+            .with_context(|| format!("{display} must have a \"members\" list of directories"))?;
+        let root = path.parent().unwrap();
+
+        let mut members: Vec<Project> = Vec::new();
+        for member in &file.members {
+            let dir = root.join(member);
+            let name = dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .with_context(|| format!("Member {member} in {display} has no directory name"))?;
+            if members
+                .iter()
+                .any(|m| m.name.as_deref() == Some(name.as_str()))
+            {
+                anyhow::bail!("{display} has more than one member named {name}");
+            }
+
+            let config_path = find_config(&dir)?.with_context(|| {
+                format!(
+                    "Member {} of {} has no config file; expected one of {}",
+                    name,
+                    display,
+                    CONFIG_FILES.join(", ")
+                )
+            })?;
+
+            members.push(Project {
+                name: Some(name),
+                root: dir,
+                config_path: Some(config_path),
+            });
+        }
+
+        Ok(Workspace {
+            path: path.to_path_buf(),
+            members,
+        })
+    }
+
+    /// The members with the given names, or every member when `all` is set
+    pub fn select(self, names: &[String], all: bool) -> anyhow::Result<Vec<Project>> {
+        if all {
+            return Ok(self.members);
+        }
+
+        let mut selected = Vec::new();
+        for name in names {
+            let Some(member) = self.members.iter().find(|m| m.name.as_ref() == Some(name)) else {
+                let known: Vec<&str> = self
+                    .members
+                    .iter()
+                    .filter_map(|m| m.name.as_deref())
+                    .collect();
+                anyhow::bail!(
+                    "No workspace member named {} in {} (members: {})",
+                    name,
+                    self.path.display(),
+                    known.join(", ")
+                );
+            };
+            selected.push(member.clone());
+        }
+        Ok(selected)
+    }
+}
@@ -0,0 +1,187 @@
+use super::{sha256, DeployRecord};
+use crate::config::{ArtifactRetention, Project};
+use fs_err as fs;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Where published builds are kept, relative to the project root
+pub const ARTIFACTS_DIR: &str = ".rit/artifacts";
+
+/// Published builds, each stored once under the SHA-256 of its contents
+#[derive(Debug, Clone)]
+pub struct Artifacts {
+    dir: PathBuf,
+}
+
+impl Artifacts {
+    pub fn new(project: &Project) -> Self {
+        Artifacts {
+            dir: project.path(ARTIFACTS_DIR),
+        }
+    }
+
+    /// The stored build with the given hash, whether or not it is still there
+    pub fn path(&self, sha256: &str) -> PathBuf {
+        self.dir.join(sha256)
+    }
+
+    /// Stores a build unless an identical one is already stored, returning its hash
+    pub fn put(&self, bytes: &[u8]) -> anyhow::Result<String> {
+        let hash = sha256(bytes);
+        let path = self.path(&hash);
+        if path.is_file() {
+            return Ok(hash);
+        }
+
+        // Written under a temporary name first, so a half written build is never mistaken for a whole one
+        fs::create_dir_all(&self.dir)?;
+        let partial = self
+            .dir
+            .join(format!("{}.{:08x}.partial", hash, fastrand::u32(..)));
+        fs::write(&partial, bytes)?;
+        fs::rename(&partial, &path)?;
+        Ok(hash)
+    }
+
+    /// Removes every build that is not among the newest `keep` publishes of a place on a branch,
+    /// then the oldest of the rest until they fit in `max_size_mb`
+    pub fn prune(
+        &self,
+        records: &[DeployRecord],
+        retention: &ArtifactRetention,
+    ) -> anyhow::Result<()> {
+        if !self.dir.is_dir() {
+            return Ok(());
+        }
+
+        let mut counts: HashMap<(&str, &str), usize> = HashMap::new();
+        let mut kept: Vec<&str> = Vec::new();
+        for record in records.iter().rev() {
+            let count = counts
+                .entry((record.branch.as_str(), record.place.as_str()))
+                .or_default();
+            if *count < retention.keep {
+                *count += 1;
+                if !kept.contains(&record.artifact_sha256.as_str()) {
+                    kept.push(&record.artifact_sha256);
+                }
+            }
+        }
+
+        if let Some(max_size_mb) = retention.max_size_mb {
+            let mut space = max_size_mb * 1024 * 1024;
+            let mut full = false;
+            kept.retain(|hash| {
+                let size = fs::metadata(self.path(hash)).map_or(0, |m| m.len());
+                full = full || size > space;
+                if !full {
+                    space -= size;
+                }
+                !full
+            });
+        }
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            if path.is_file() && !kept.contains(&name.as_str()) {
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored(artifacts: &Artifacts, hashes: &[String]) -> Vec<bool> {
+        hashes.iter().map(|h| artifacts.path(h).is_file()).collect()
+    }
+
+    #[test]
+    fn prune_keeps_the_newest_builds_of_each_place_and_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifacts = Artifacts {
+            dir: dir.path().to_path_buf(),
+        };
+        let builds: Vec<String> = (0..5)
+            .map(|i| artifacts.put(format!("build {i}").as_bytes()).unwrap())
+            .collect();
+
+        let records = vec![
+            DeployRecord::test("main", "lobby").artifact(&builds[0]),
+            DeployRecord::test("main", "lobby").artifact(&builds[1]),
+            DeployRecord::test("main", "lobby").artifact(&builds[2]),
+            DeployRecord::test("main", "arena").artifact(&builds[3]),
+            DeployRecord::test("qa", "lobby").artifact(&builds[0]),
+            DeployRecord::test("main", "arena").artifact(&builds[4]),
+        ];
+        let retention = ArtifactRetention {
+            keep: 2,
+            max_size_mb: None,
+        };
+        artifacts.prune(&records, &retention).unwrap();
+
+        // build 0 is old on main but still among the newest on qa
+        assert_eq!(stored(&artifacts, &builds), [true, true, true, true, true]);
+
+        let retention = ArtifactRetention {
+            keep: 1,
+            max_size_mb: None,
+        };
+        artifacts.prune(&records, &retention).unwrap();
+        assert_eq!(
+            stored(&artifacts, &builds),
+            [true, false, true, false, true]
+        );
+    }
+
+    #[test]
+    fn prune_keeps_shared_builds_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifacts = Artifacts {
+            dir: dir.path().to_path_buf(),
+        };
+        let shared = artifacts.put(b"shared").unwrap();
+        let old = artifacts.put(b"old").unwrap();
+
+        let records = vec![
+            DeployRecord::test("main", "lobby").artifact(&old),
+            DeployRecord::test("main", "lobby").artifact(&shared),
+            DeployRecord::test("main", "arena").artifact(&shared),
+        ];
+        let retention = ArtifactRetention {
+            keep: 1,
+            max_size_mb: None,
+        };
+        artifacts.prune(&records, &retention).unwrap();
+
+        assert_eq!(stored(&artifacts, &[shared.clone(), old]), [true, false]);
+        assert_eq!(artifacts.put(b"shared").unwrap(), shared);
+    }
+
+    #[test]
+    fn prune_drops_the_oldest_builds_over_the_size_cap() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifacts = Artifacts {
+            dir: dir.path().to_path_buf(),
+        };
+        let builds: Vec<String> = (0..4u8)
+            .map(|i| artifacts.put(&vec![i; 400 * 1024]).unwrap())
+            .collect();
+
+        let records: Vec<DeployRecord> = builds
+            .iter()
+            .map(|hash| DeployRecord::test("main", "lobby").artifact(hash))
+            .collect();
+        let retention = ArtifactRetention {
+            keep: 10,
+            max_size_mb: Some(1),
+        };
+        artifacts.prune(&records, &retention).unwrap();
+
+        assert_eq!(stored(&artifacts, &builds), [false, false, true, true]);
+    }
+}
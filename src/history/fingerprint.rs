@@ -0,0 +1,133 @@
+use super::sha256;
+use rbx_dom_weak::types::{Ref, Variant};
+use rbx_dom_weak::WeakDom;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A hash of what a place file contains rather than how it was written, so two builds of the
+/// same sources match even though rojo gives their instances fresh referents and ids each time;
+/// a file that cannot be read as a place falls back to the hash of its bytes
+pub fn fingerprint(path: &Path, bytes: &[u8]) -> String {
+    let dom = match path.extension().and_then(|e| e.to_str()) {
+        Some("rbxlx") => rbx_xml::from_reader_default(bytes).ok(),
+        _ => rbx_binary::from_reader(bytes).ok(),
+    };
+
+    match dom {
+        Some(dom) => format!("dom:{}", dom_hash(&dom)),
+        None => format!("bytes:{}", sha256(bytes)),
+    }
+}
+
+fn dom_hash(dom: &WeakDom) -> String {
+    // Referents are replaced by the position of their instance in the tree
+    let order: Vec<Ref> = dom
+        .descendants()
+        .map(|instance| instance.referent())
+        .collect();
+    let positions: HashMap<Ref, usize> = order.iter().enumerate().map(|(i, r)| (*r, i)).collect();
+
+    let mut hasher = Sha256::new();
+    let mut write = |text: &str| {
+        hasher.update((text.len() as u64).to_le_bytes());
+        hasher.update(text.as_bytes());
+    };
+
+    for referent in &order {
+        let instance = dom.get_by_ref(*referent).unwrap();
+        write(&instance.class);
+        write(&instance.name);
+        write(
+            &positions
+                .get(&instance.parent())
+                .map_or(-1, |p| *p as i64)
+                .to_string(),
+        );
+
+        let mut properties: Vec<(&String, &Variant)> = instance
+            .properties
+            .iter()
+            .filter(|(_, value)| !matches!(value, Variant::UniqueId(_)))
+            .collect();
+        properties.sort_by_key(|(key, _)| *key);
+
+        for (key, value) in properties {
+            write(key);
+            match value {
+                Variant::Ref(target) => {
+                    write(&positions.get(target).map_or(-1, |p| *p as i64).to_string())
+                }
+                value => write(&format!("{value:?}")),
+            }
+        }
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rbx_dom_weak::types::UniqueId;
+    use rbx_dom_weak::InstanceBuilder;
+
+    /// A small place whose referents are new every time and whose ids come from `seed`
+    fn place(seed: i64, anchored: bool) -> WeakDom {
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let workspace = dom.insert(dom.root_ref(), InstanceBuilder::new("Workspace"));
+        let floor = dom.insert(
+            workspace,
+            InstanceBuilder::new("Part")
+                .with_name("Floor")
+                .with_property("Anchored", anchored)
+                .with_property("UniqueId", UniqueId::new(1, 0, seed)),
+        );
+        dom.insert(
+            workspace,
+            InstanceBuilder::new("ObjectValue")
+                .with_name("Spawn")
+                .with_property("Value", floor)
+                .with_property("UniqueId", UniqueId::new(2, 0, seed)),
+        );
+        dom
+    }
+
+    fn binary(dom: &WeakDom) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        rbx_binary::to_writer(&mut bytes, dom, dom.root().children()).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn ignores_referents_and_unique_ids() {
+        let a = place(1, true);
+        let b = place(2, true);
+        assert_ne!(a.root_ref(), b.root_ref());
+
+        assert_eq!(dom_hash(&a), dom_hash(&b));
+        let path = Path::new("place.rbxl");
+        let (a, b) = (binary(&a), binary(&b));
+        assert_ne!(a, b);
+        assert_eq!(fingerprint(path, &a), fingerprint(path, &b));
+        assert!(fingerprint(path, &a).starts_with("dom:"));
+    }
+
+    #[test]
+    fn changes_with_properties() {
+        assert_ne!(dom_hash(&place(1, true)), dom_hash(&place(1, false)));
+    }
+
+    #[test]
+    fn falls_back_to_the_bytes() {
+        let path = Path::new("place.rbxl");
+        assert_eq!(
+            fingerprint(path, b"not a place"),
+            format!("bytes:{}", sha256(b"not a place"))
+        );
+    }
+}
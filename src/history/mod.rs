@@ -0,0 +1,158 @@
+mod artifacts;
+mod fingerprint;
+
+pub use artifacts::{Artifacts, ARTIFACTS_DIR};
+pub use fingerprint::fingerprint;
+
+use crate::config::{OutputFormat, Project, VersionType};
+use anyhow::Context;
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Where the deploy history of a project is kept, relative to its root
+pub const HISTORY_FILE: &str = ".rit/deployments.jsonl";
+
+/// One place published by a deploy, as written to the history file
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployRecord {
+    /// Shared by every place published by the same deploy
+    pub deploy_id: String,
+    pub timestamp: String,
+    pub branch: String,
+    pub universe_id: u64,
+    pub place: String,
+    pub place_id: u64,
+    pub version: u64,
+    pub version_type: VersionType,
+    pub artifact_sha256: String,
+    /// The format of the build, which decides how it is uploaded again by a rollback
+    #[serde(default)]
+    pub output: OutputFormat,
+    /// The fingerprint of the build, used to skip places that have not changed since
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// The deploy whose build was republished, for records made by `rit rollback`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rollback_of: Option<String>,
+}
+
+#[cfg(test)]
+impl DeployRecord {
+    /// A record of the first published version of a place, for tests to build on
+    pub fn test(branch: &str, place: &str) -> Self {
+        DeployRecord {
+            deploy_id: String::new(),
+            timestamp: String::new(),
+            branch: branch.to_string(),
+            universe_id: 1,
+            place: place.to_string(),
+            place_id: 2,
+            version: 1,
+            version_type: VersionType::Published,
+            artifact_sha256: format!("{place}-1"),
+            output: OutputFormat::Rbxl,
+            content_hash: None,
+            commit: None,
+            message: None,
+            rollback_of: None,
+        }
+    }
+
+    /// Made by the given deploy, publishing the given version of a build named after both
+    pub fn deployed(self, deploy_id: &str, version: u64) -> Self {
+        DeployRecord {
+            deploy_id: deploy_id.to_string(),
+            version,
+            artifact_sha256: format!("{}-{version}", self.place),
+            ..self
+        }
+    }
+
+    pub fn artifact(self, artifact_sha256: &str) -> Self {
+        DeployRecord {
+            artifact_sha256: artifact_sha256.to_string(),
+            ..self
+        }
+    }
+}
+
+/// The deploy history of a project, one JSON record per line, oldest first
+#[derive(Debug, Clone)]
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(project: &Project) -> Self {
+        History {
+            path: project.path(HISTORY_FILE),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, record: &DeployRecord) -> anyhow::Result<()> {
+        fs::create_dir_all(self.path.parent().unwrap())?;
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        // Places publishing side by side each write a whole line at once, so lines never interleave
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Every record, oldest first; a project that never deployed has none
+    pub fn read(&self) -> anyhow::Result<Vec<DeployRecord>> {
+        if !self.path.is_file() {
+            return Ok(Vec::new());
+        }
+
+        fs::read_to_string(&self.path)?
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line).with_context(|| {
+                    format!(
+                        "Line {} of {} is not a deploy record",
+                        i + 1,
+                        self.path.display()
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// A short random id for a deploy, like an abbreviated commit hash
+pub fn new_deploy_id() -> String {
+    format!("{:08x}", fastrand::u32(..))
+}
+
+/// The current time in RFC 3339, to the second
+pub fn timestamp() -> String {
+    humantime::format_rfc3339_seconds(SystemTime::now()).to_string()
+}
+
+/// The SHA-256 of some bytes as lowercase hex
+pub fn sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
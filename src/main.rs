@@ -1,15 +1,17 @@
 mod cli;
 mod color;
 mod config;
+mod history;
 mod rbx;
 
 use clap::Parser;
 use cli::Cli;
-use dotenv::dotenv;
+use dotenvy::dotenv;
 use std::process;
 
 #[tokio::main]
 async fn main() {
+    config::snapshot_shell();
     dotenv().ok();
     let args = Cli::parse();
 
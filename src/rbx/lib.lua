@@ -1,10 +1,15 @@
 ---@diagnostic disable: undefined-global
-local libdir = ...
-libdir = libdir:match("[^/]+")
-
-local CONFIG_PATH = ("%s/%s"):format(libdir, "config.json")
 local NO_PLACES_ERROR = "Config does not have any places"
 
+local DEFAULT_EXTRACT = { "Workspace/*", "Lighting" }
+local DEFAULT_STRIP = {
+	"Script",
+	"LocalScript",
+	"ModuleScript",
+	"CoreScript", -- this may never happen but better safe than sorry
+	"Camera", -- very annoying when rojo jerks your camera around
+}
+
 local DEFAULT_PROJECT_FILE = "default.project.json"
 local DEFAULT_PROJECT_CONTENT = {
 	["name"] = "Untitled Project",
@@ -30,25 +35,79 @@ local function jsonDecode(str)
 	end
 end
 
-local maps = isFile(CONFIG_PATH) and jsonDecode(remodel.readFile(CONFIG_PATH)) or {}
+local function splitPath(path)
+	local segments = {}
+	for segment in path:gmatch("[^/]+") do
+		table.insert(segments, segment)
+	end
+	return segments
+end
+
+local function sanitizeName(name)
+	return (name:gsub("[^%w_]", ""))
+end
+
+-- an extract entry ending in /* writes each child of the instance, otherwise the instance is written whole
+local function parseExtract(entry)
+	local base = entry:match("^(.*)/%*$")
+	if base then
+		return splitPath(base), true
+	end
+	return splitPath(entry), false
+end
+
+local function findInstance(root, segments)
+	local instance = root
+	for _, segment in ipairs(segments) do
+		instance = instance:FindFirstChild(segment)
+		if not instance then
+			return nil
+		end
+	end
+	return instance
+end
+
+-- where an extract entry is written: a directory of children, or a single model file
+local function extractPath(dir, entry)
+	local segments, children = parseExtract(entry)
+	if children then
+		return ("%s/%s"):format(dir, table.concat(segments, "/"))
+	end
+
+	local name = table.remove(segments)
+	table.insert(segments, 1, dir)
+	return ("%s/%s.rbxm"):format(table.concat(segments, "/"), sanitizeName(name))
+end
+
+-- the config is resolved by rit, so every supported config format ends up here as JSON
+local maps = jsonDecode([==[{{config}}]==]) or {}
 
 function maps.getProjectFileName(name)
 	return ("%s.project.json"):format(name)
 end
 
-function maps.removeUnwantedItems(root)
+function maps:getLayout(name)
+	local places = self.import and self.import.places or {}
+	local place = places[name] or {}
+
+	return {
+		dir = place.output or ("maps/%s"):format(name),
+		extract = place.extract or DEFAULT_EXTRACT,
+		strip = place.strip or DEFAULT_STRIP,
+	}
+end
+
+function maps.removeUnwantedItems(root, classes)
 	local trash = {}
+	local unwanted = {}
+	for _, className in ipairs(classes or DEFAULT_STRIP) do
+		unwanted[className] = true
+	end
 
 	print(("Scanning %s for unwanted instances..."):format(root.Name))
 
 	for _, instance in ipairs(root:GetDescendants()) do -- remodel does not support roblox's :IsA method yet
-		if
-			instance.ClassName == "Script"
-			or instance.ClassName == "LocalScript"
-			or instance.ClassName == "ModuleScript"
-			or instance.ClassName == "CoreScript" -- this may never happen but better safe than sorry
-			or instance.ClassName == "Camera" -- very annoying when rojo jerks your camera around
-		then
+		if unwanted[instance.ClassName] then
 			table.insert(trash, instance)
 			print(("Marked %s '%s' for deletion"):format(instance.ClassName, instance.Name))
 		end
@@ -71,9 +130,9 @@ function maps:_getGame(options)
 	if options.name then
 		assert(self.import.places, NO_PLACES_ERROR)
 
-		local placeId = self.import.places[options.name]
-		if placeId then
-			return remodel.readPlaceAsset(placeId)
+		local place = self.import.places[options.name]
+		if place then
+			return remodel.readPlaceAsset(place.id)
 		end
 	end
 
@@ -89,11 +148,11 @@ function maps:_getGame(options)
 end
 
 function maps:loadPlace(options)
-	return self.removeUnwantedItems(self:_getGame(options))
+	return self.removeUnwantedItems(self:_getGame(options), options.strip)
 end
 
 function maps:writeModelFile(model, path)
-	local sanitizedName = model.Name:gsub("[^%w_]", "")
+	local sanitizedName = sanitizeName(model.Name)
 
 	if self.usedNames[sanitizedName] then
 		self.usedNames[sanitizedName] = self.usedNames[sanitizedName] + 1
@@ -102,11 +161,13 @@ function maps:writeModelFile(model, path)
 		self.usedNames[sanitizedName] = 0
 	end
 
-	local filePath = ("%s/%s.rbxm"):format(path, sanitizedName)
+	self:writeModelTo(model, ("%s/%s.rbxm"):format(path, sanitizedName))
+end
 
+function maps:writeModelTo(model, filePath)
 	print(("Importing %s"):format(filePath))
 
-	remodel.createDirAll(path)
+	remodel.createDirAll((filePath:gsub("/[^/]*$", "")))
 	remodel.writeModelFile(model, filePath)
 end
 
@@ -146,21 +207,30 @@ function maps:importLocalAssets(path)
 	self:_importGameAssets({ path = path })
 end
 
-function maps.getMapDir(name)
-	return ("maps/%s"):format(name)
-end
-
 local function deserializeProjectFile(projectFile)
 	local project = jsonDecode(remodel.readFile(projectFile))
 
 	project.tree = project.tree or DEFAULT_PROJECT_CONTENT.tree
 	project.tree["$className"] = project.tree["$className"] or "DataModel"
-	project.tree.Workspace = project.tree.Workspace or {}
-	project.tree.Lighting = project.tree.Lighting or {}
 
 	return project
 end
 
+-- finds the node of an instance path in a project tree, adding folders for any missing parents
+local function ensureNode(tree, segments)
+	local node = tree
+	for depth, segment in ipairs(segments) do
+		if not node[segment] then
+			-- services are recognized by rojo from their name, and the last node takes its
+			-- class from the file at its $path, so only the parents in between need one
+			local isParent = depth > 1 and depth < #segments
+			node[segment] = isParent and { ["$className"] = "Folder" } or {}
+		end
+		node = node[segment]
+	end
+	return node
+end
+
 function maps:_defaultProjectFileContent(name)
 	local projectFileName = self.getProjectFileName(name)
 
@@ -176,23 +246,21 @@ function maps:_defaultProjectFileContent(name)
 end
 
 function maps:generateProjectFile(name)
-	local dir = self.getMapDir(name)
-	local workspacePath = ("%s/Workspace"):format(dir)
-	local lightingPath = ("%s/Lighting.rbxm"):format(dir)
+	local layout = self:getLayout(name)
 	local projectFileName = self.getProjectFileName(name)
 	local content = self:_defaultProjectFileContent(name)
 	local changed = false
 
 	content.name = name
 
-	if content.tree.Workspace["$path"] ~= workspacePath then
-		content.tree.Workspace["$path"] = workspacePath
-		changed = true
-	end
+	for _, entry in ipairs(layout.extract) do
+		local node = ensureNode(content.tree, (parseExtract(entry)))
+		local path = extractPath(layout.dir, entry)
 
-	if content.tree.Lighting["$path"] ~= lightingPath then
-		content.tree.Lighting["$path"] = lightingPath
-		changed = true
+		if node["$path"] ~= path then
+			node["$path"] = path
+			changed = true
+		end
 	end
 
 	if changed then
@@ -210,7 +278,8 @@ end
 function maps:_importGameMap(game, name)
 	assert(name and name ~= "", "name must not be nil or empty")
 
-	local dir = self.getMapDir(name)
+	local layout = self:getLayout(name)
+	local dir = layout.dir
 
 	if pcall(remodel.isDir, dir) then
 		-- we want a completely new copy of the imported map
@@ -218,11 +287,20 @@ function maps:_importGameMap(game, name)
 		os.execute(("rm -rd '%s'"):format(dir))
 	end
 
-	self:writeModelFile(game.Lighting, dir)
-
-	local workspaceDir = ("%s/Workspace"):format(dir)
-	for _, instance in ipairs(game.Workspace:GetChildren()) do
-		self:writeModelFile(instance, workspaceDir)
+	for _, entry in ipairs(layout.extract) do
+		local segments, children = parseExtract(entry)
+		local instance = findInstance(game, segments)
+
+		if not instance then
+			print(("No %s found in %s"):format(table.concat(segments, "/"), name))
+		elseif children then
+			local path = extractPath(dir, entry)
+			for _, child in ipairs(instance:GetChildren()) do
+				self:writeModelFile(child, path)
+			end
+		else
+			self:writeModelTo(instance, extractPath(dir, entry))
+		end
 	end
 
 	self:generateProjectFile(name)
@@ -233,11 +311,12 @@ function maps:importLocalMap(path, name)
 		name = nil
 	end
 
-	self:_importGameMap(self:loadPlace({ path = path }), name or "file_import")
+	name = name or "file_import"
+	self:_importGameMap(self:loadPlace({ path = path, strip = self:getLayout(name).strip }), name)
 end
 
 function maps:importMap(name)
-	self:_importGameMap(self:loadPlace({ name = name }), name)
+	self:_importGameMap(self:loadPlace({ name = name, strip = self:getLayout(name).strip }), name)
 end
 
 function maps:importAllMaps()
@@ -1,4 +1,7 @@
+use super::retry::with_rate_limit_retry;
+use crate::cli::output::Output;
 use crate::color::Color;
+use crate::config::RetryPolicy;
 use clap::Parser;
 use rbxcloud::rbx::{RbxCloud, UniverseId};
 
@@ -6,26 +9,34 @@ use rbxcloud::rbx::{RbxCloud, UniverseId};
 pub struct Message {
     pub api_key: String,
     pub universe_id: u64,
+    #[clap(skip)]
+    pub retry: RetryPolicy,
 }
 
 impl Message {
-    pub fn new(api_key: &str, universe_id: u64) -> Message {
+    pub fn new(api_key: &str, universe_id: u64, retry: RetryPolicy) -> Message {
         Message {
             api_key: api_key.to_string(),
             universe_id,
+            retry,
         }
     }
 
-    pub async fn publish(&self, topic: &str, data: &str) {
+    pub async fn publish(&self, topic: &str, data: &str) -> anyhow::Result<()> {
         let cloud = RbxCloud::new(&self.api_key, UniverseId(self.universe_id));
         let messaging = cloud.messaging(topic);
 
-        messaging.publish(data).await.ok();
+        let label = format!("message to {topic}");
+        with_rate_limit_retry(&self.retry, &label, &Output::default(), || {
+            messaging.publish(data)
+        })
+        .await?;
         println!(
             "{} message \"{}\" with topic: {}",
             Color::green().pad("Published"),
             data,
             topic
         );
+        Ok(())
     }
 }
@@ -1,6 +1,8 @@
 mod message;
 mod remodel;
+mod retry;
 mod universe;
 pub use message::*;
 pub use remodel::*;
+pub use retry::*;
 pub use universe::*;
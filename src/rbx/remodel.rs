@@ -1,4 +1,6 @@
+use crate::config::ConfigData;
 use clap::Parser;
+use std::path::{Path, PathBuf};
 use std::{io::Write, process::Command};
 use tempfile::Builder;
 
@@ -7,22 +9,31 @@ static LIBRARY_TEMPLATE: &str = include_str!("lib.lua");
 #[derive(Debug, Parser)]
 pub struct Remodel {
     auth: String,
+    config: String,
+    root: PathBuf,
 }
 
 impl Remodel {
-    pub fn new(auth: String) -> Remodel {
-        Remodel { auth }
+    pub fn new(auth: String, config: &ConfigData, root: &Path) -> Remodel {
+        Remodel {
+            auth,
+            config: serde_json::to_string(config).unwrap(),
+            root: root.to_path_buf(),
+        }
     }
 
     pub fn run(&self, method: &str, args: &[String]) {
-        let complete_source = LIBRARY_TEMPLATE.replace("{{method}}", method).replace(
-            "{{args}}",
-            &args
-                .iter()
-                .map(|arg| format!("\"{}\"", arg))
-                .collect::<Vec<_>>()
-                .join(", "),
-        );
+        let complete_source = LIBRARY_TEMPLATE
+            .replace("{{method}}", method)
+            .replace(
+                "{{args}}",
+                &args
+                    .iter()
+                    .map(|arg| format!("\"{}\"", arg))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+            .replace("{{config}}", &self.config);
 
         let mut temp_file = Builder::new()
             .prefix("rit-")
@@ -41,6 +52,7 @@ impl Remodel {
         Command::new("sh")
             .arg("-c")
             .arg(remodel_command)
+            .current_dir(&self.root)
             .output()
             .expect("failed to execute process");
     }
@@ -0,0 +1,365 @@
+use crate::cli::output::Output;
+use crate::color::Color;
+use crate::config::RetryPolicy;
+use rbxcloud::rbx::datastore::DataStoreErrorCode;
+use rbxcloud::rbx::error::Error;
+use std::fmt::Display;
+use std::future::Future;
+use std::time::{Duration, SystemTime};
+
+/// An error from an Open Cloud request that may go away if the request is made again
+pub trait Retryable: Display {
+    /// The HTTP status the request failed with, if it got a response at all
+    fn status_code(&self) -> Option<u16>;
+
+    /// How long the server asked to wait before the next request
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Rate limits, server errors and dropped connections are worth retrying
+    fn is_transient(&self) -> bool {
+        self.status_code()
+            .is_some_and(|code| code == 429 || code >= 500)
+    }
+}
+
+impl Retryable for Error {
+    fn status_code(&self) -> Option<u16> {
+        match self {
+            Error::HttpStatusError { code, .. } => Some(*code),
+            Error::ReqwestError(err) => err.status().map(|status| status.as_u16()),
+            Error::DataStoreError(response) => response
+                .error_details
+                .iter()
+                .any(|detail| {
+                    matches!(
+                        detail.datastore_error_code,
+                        DataStoreErrorCode::TooManyRequests
+                    )
+                })
+                .then_some(429),
+            _ => None,
+        }
+    }
+
+    fn is_transient(&self) -> bool {
+        match self {
+            Error::ReqwestError(err) if err.is_timeout() || err.is_connect() => true,
+            _ => self
+                .status_code()
+                .is_some_and(|code| code == 429 || code >= 500),
+        }
+    }
+}
+
+/// Makes a request until it succeeds, fails for good or runs out of attempts, logging each retry
+/// to `out`; `label` names the request in the log and the final error, such as
+/// `publish of lobby (123)`
+pub async fn with_retry<T, E, F, Fut>(
+    policy: &RetryPolicy,
+    label: &str,
+    out: &Output,
+    request: F,
+) -> anyhow::Result<T>
+where
+    E: Retryable,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_when(policy, label, out, request, E::is_transient).await
+}
+
+/// Like `with_retry`, for requests that must not take effect twice, such as an increment or a
+/// message; a server error or timeout may come after the request went through, so only rate
+/// limited requests are retried
+pub async fn with_rate_limit_retry<T, E, F, Fut>(
+    policy: &RetryPolicy,
+    label: &str,
+    out: &Output,
+    request: F,
+) -> anyhow::Result<T>
+where
+    E: Retryable,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_when(policy, label, out, request, |err: &E| {
+        err.status_code() == Some(429)
+    })
+    .await
+}
+
+async fn retry_when<T, E, F, Fut>(
+    policy: &RetryPolicy,
+    label: &str,
+    out: &Output,
+    mut request: F,
+    should_retry: impl Fn(&E) -> bool,
+) -> anyhow::Result<T>
+where
+    E: Retryable,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let attempts = policy.attempts.max(1);
+    let mut attempt = 1;
+
+    loop {
+        let err = match request().await {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+
+        if !should_retry(&err) || attempt >= attempts {
+            let tries = match attempt {
+                1 => "1 attempt".to_string(),
+                n => format!("{n} attempts"),
+            };
+            match err.status_code() {
+                Some(code) => {
+                    anyhow::bail!("The {label} failed with status {code} after {tries}: {err}")
+                }
+                None => anyhow::bail!("The {label} failed after {tries}: {err}"),
+            }
+        }
+
+        // A server asking for a longer wait than the maximum delay is not waited on for longer
+        let delay = match err.retry_after() {
+            Some(delay) => delay.min(Duration::from_millis(policy.max_delay_ms)),
+            None => backoff(policy, attempt),
+        };
+        out.status(
+            Color::blue(),
+            "Retrying",
+            format!(
+                "{} in {:.1}s, attempt {} of {} failed: {}",
+                label,
+                delay.as_secs_f64(),
+                attempt,
+                attempts,
+                err
+            ),
+        );
+
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// The delay after a failed attempt: the base delay doubled for every earlier retry, capped at
+/// the maximum, of which a random half is taken off so parallel requests do not retry in step
+fn backoff(policy: &RetryPolicy, attempt: u32) -> Duration {
+    let doubled = policy
+        .base_delay_ms
+        .saturating_mul(1 << (attempt - 1).min(32));
+    let ceiling = doubled.min(policy.max_delay_ms);
+    let half = ceiling / 2;
+    Duration::from_millis(ceiling - half + fastrand::u64(0..=half))
+}
+
+/// Reads a `Retry-After` header, given either as a number of seconds or as an HTTP date
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::fmt;
+
+    #[derive(Debug)]
+    struct Failure {
+        code: Option<u16>,
+        retry_after: Option<Duration>,
+    }
+
+    fn failure(code: Option<u16>) -> Failure {
+        Failure {
+            code,
+            retry_after: None,
+        }
+    }
+
+    impl fmt::Display for Failure {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "request failed")
+        }
+    }
+
+    impl Retryable for Failure {
+        fn status_code(&self) -> Option<u16> {
+            self.code
+        }
+
+        fn retry_after(&self) -> Option<Duration> {
+            self.retry_after
+        }
+    }
+
+    fn policy(attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            attempts,
+            base_delay_ms: 0,
+            max_delay_ms: 0,
+        }
+    }
+
+    /// Fails with each of `failures` in turn, then succeeds, returning the result and the number
+    /// of requests made
+    async fn attempt(
+        policy: &RetryPolicy,
+        rate_limit_only: bool,
+        failures: Vec<Failure>,
+    ) -> (anyhow::Result<u32>, u32) {
+        let made = Cell::new(0);
+        let mut failures = failures.into_iter();
+        let request = || {
+            made.set(made.get() + 1);
+            let result = match failures.next() {
+                Some(err) => Err(err),
+                None => Ok(made.get()),
+            };
+            async move { result }
+        };
+
+        let out = Output::default();
+        let result = match rate_limit_only {
+            true => with_rate_limit_retry(policy, "test", &out, request).await,
+            false => with_retry(policy, "test", &out, request).await,
+        };
+        (result, made.get())
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum_with_jitter() {
+        let policy = RetryPolicy {
+            attempts: 10,
+            base_delay_ms: 100,
+            max_delay_ms: 1000,
+        };
+
+        for _ in 0..50 {
+            for (attempt, ceiling) in [
+                (1, 100),
+                (2, 200),
+                (3, 400),
+                (4, 800),
+                (5, 1000),
+                (40, 1000),
+            ] {
+                let delay = backoff(&policy, attempt).as_millis() as u64;
+                assert!(
+                    (ceiling / 2..=ceiling).contains(&delay),
+                    "attempt {attempt} waited {delay}ms"
+                );
+            }
+        }
+        assert_eq!(
+            backoff(
+                &RetryPolicy {
+                    base_delay_ms: 0,
+                    ..policy
+                },
+                3
+            ),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn parses_retry_after_seconds() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 0 "), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon"), None);
+        assert_eq!(parse_retry_after("-5"), None);
+    }
+
+    #[test]
+    fn parses_retry_after_dates() {
+        let later = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(60));
+        let delay = parse_retry_after(&later).unwrap();
+        assert!(
+            (Duration::from_secs(55)..=Duration::from_secs(60)).contains(&delay),
+            "{delay:?}"
+        );
+
+        // A date that has already passed means there is nothing left to wait for
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[tokio::test]
+    async fn retries_transient_failures() {
+        let (result, made) = attempt(
+            &policy(4),
+            false,
+            vec![failure(Some(503)), failure(Some(429)), failure(Some(502))],
+        )
+        .await;
+        assert_eq!(result.unwrap(), 4);
+        assert_eq!(made, 4);
+
+        let (result, made) = attempt(&policy(4), false, vec![failure(Some(404))]).await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "The test failed with status 404 after 1 attempt: request failed"
+        );
+        assert_eq!(made, 1);
+
+        let (result, made) = attempt(&policy(4), false, vec![failure(None)]).await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "The test failed after 1 attempt: request failed"
+        );
+        assert_eq!(made, 1);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_the_last_attempt() {
+        let failures = (0..3).map(|_| failure(Some(500))).collect();
+        let (result, made) = attempt(&policy(2), false, failures).await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "The test failed with status 500 after 2 attempts: request failed"
+        );
+        assert_eq!(made, 2);
+    }
+
+    #[tokio::test]
+    async fn retries_only_rate_limits_when_asked() {
+        let (result, made) = attempt(&policy(4), true, vec![failure(Some(429))]).await;
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(made, 2);
+
+        let (result, made) = attempt(&policy(4), true, vec![failure(Some(503))]).await;
+        assert!(result.is_err());
+        assert_eq!(made, 1);
+    }
+
+    #[tokio::test]
+    async fn caps_retry_after_at_the_maximum_delay() {
+        let rate_limited = Failure {
+            code: Some(429),
+            retry_after: Some(Duration::from_secs(3600)),
+        };
+        let policy = policy(2);
+        let request = attempt(&policy, false, vec![rate_limited]);
+        let (result, _) = tokio::time::timeout(Duration::from_secs(5), request)
+            .await
+            .unwrap();
+        assert_eq!(result.unwrap(), 2);
+    }
+}
@@ -1,45 +1,153 @@
-use crate::color::Color;
+use super::retry::{parse_retry_after, with_retry, Retryable};
+use crate::cli::output::Output;
+use crate::config::{OutputFormat, RetryPolicy, VersionType};
 use clap::Parser;
-use rbxcloud::rbx::{PlaceId, PublishVersionType, RbxCloud, UniverseId};
-use serde_json::Value;
+use reqwest::header::RETRY_AFTER;
+use serde::Deserialize;
+use std::fmt;
+use std::time::Duration;
 
 #[derive(Debug, Parser)]
 pub struct Universe {
     pub api_key: String,
     pub universe_id: u64,
+    #[clap(skip)]
+    pub retry: RetryPolicy,
+}
+
+/// A publish request that failed, with the wait the server asked for if it was rate limited
+#[derive(Debug)]
+pub enum PublishError {
+    Status {
+        code: u16,
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    Request(reqwest::Error),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PublishResponse {
+    version_number: u64,
 }
 
 impl Universe {
-    pub fn new(api_key: &str, universe_id: u64) -> Universe {
+    pub fn new(api_key: &str, universe_id: u64, retry: RetryPolicy) -> Universe {
         Universe {
             api_key: api_key.to_string(),
             universe_id,
+            retry,
+        }
+    }
+
+    /// Publishes a place file of the given format, returning the new version number; retries are
+    /// logged to `out`
+    pub async fn publish(
+        &self,
+        out: &Output,
+        name: &str,
+        path: &str,
+        format: OutputFormat,
+        place_id: u64,
+        version_type: VersionType,
+    ) -> anyhow::Result<u64> {
+        let bytes = fs_err::read(path)?;
+        let client = reqwest::Client::new();
+        let label = format!("publish of {name} ({place_id})");
+
+        with_retry(&self.retry, &label, out, || {
+            self.publish_once(&client, &bytes, format, place_id, version_type)
+        })
+        .await
+    }
+
+    async fn publish_once(
+        &self,
+        client: &reqwest::Client,
+        bytes: &[u8],
+        format: OutputFormat,
+        place_id: u64,
+        version_type: VersionType,
+    ) -> Result<u64, PublishError> {
+        let url = format!(
+            "https://apis.roblox.com/universes/v1/{}/places/{}/versions?versionType={:?}",
+            self.universe_id, place_id, version_type
+        );
+        let res = client
+            .post(url)
+            .header("x-api-key", &self.api_key)
+            .header("Content-Type", format.content_type())
+            .body(bytes.to_vec())
+            .send()
+            .await?;
+
+        let status = res.status();
+        if !status.is_success() {
+            let code = status.as_u16();
+            let retry_after = res
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after);
+            let message = match code {
+                400 => "invalid request or file content",
+                401 => "api key not valid for operation",
+                403 => "publish not allowed on place",
+                404 => "place or universe does not exist",
+                409 => "place not part of the universe",
+                429 => "too many requests",
+                500 => "internal server error",
+                _ => status.canonical_reason().unwrap_or_default(),
+            };
+            return Err(PublishError::Status {
+                code,
+                message: message.to_string(),
+                retry_after,
+            });
+        }
+
+        let body = res.json::<PublishResponse>().await?;
+        Ok(body.version_number)
+    }
+}
+
+impl From<reqwest::Error> for PublishError {
+    fn from(err: reqwest::Error) -> Self {
+        PublishError::Request(err)
+    }
+}
+
+impl fmt::Display for PublishError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PublishError::Status { message, .. } => write!(f, "{message}"),
+            PublishError::Request(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl Retryable for PublishError {
+    fn status_code(&self) -> Option<u16> {
+        match self {
+            PublishError::Status { code, .. } => Some(*code),
+            PublishError::Request(err) => err.status().map(|status| status.as_u16()),
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            PublishError::Status { retry_after, .. } => *retry_after,
+            PublishError::Request(_) => None,
         }
     }
 
-    pub async fn publish(&self, path: &str, place_to_publish: (&String, &Value)) {
-        let place_name = place_to_publish.0;
-        let place_id = place_to_publish.1.as_u64().unwrap();
-
-        let publish_version_type = PublishVersionType::Published;
-        let cloud = RbxCloud::new(&self.api_key, UniverseId(self.universe_id));
-        let experience = cloud.experience(PlaceId(place_id));
-
-        // Publish place:
-        let publish_result = experience.publish(path, publish_version_type).await;
-        match publish_result {
-            Ok(result) => {
-                println!(
-                    "{} {} ({}) with version number: {}",
-                    Color::green().pad("Published"),
-                    place_name,
-                    place_id,
-                    result.version_number
-                );
-            }
-            Err(e) => {
-                eprintln!("{e:?}");
-            }
+    fn is_transient(&self) -> bool {
+        match self {
+            PublishError::Request(err) if err.is_timeout() || err.is_connect() => true,
+            _ => self
+                .status_code()
+                .is_some_and(|code| code == 429 || code >= 500),
         }
     }
 }